@@ -1,40 +1,106 @@
+use std::path::Path;
+
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use wmi::{COMLibrary, WMIConnection};
 
-use crate::models::{CheckIn, Drive};
-
-pub fn collect() -> Result<CheckIn> {
-    let hostname = std::env::var("COMPUTERNAME").unwrap_or_else(|_| "UNKNOWN".to_string());
+use crate::config::Config;
+use crate::inventory;
+use crate::models::{CheckIn, Drive, HostnameSource, Session, SystemIdentity};
+use crate::{osv, sbom, serial};
 
+/// Collect a check-in. `last_checkin` is when the previous check-in was sent successfully, if
+/// any; login history is reported from that point on.
+pub fn collect(cfg: &Config, last_checkin: Option<DateTime<Utc>>) -> Result<CheckIn> {
+    let root = Path::new("/");
     let ip_address = primary_ipv4().unwrap_or_else(|| "0.0.0.0".to_string());
 
     let com = COMLibrary::new().context("Initialize COM library failed")?;
     let wmi = WMIConnection::new(com.into()).context("WMI connection failed")?;
 
-    // Logged in user
+    // Logged in user and host name
     #[derive(serde::Deserialize, Debug)]
     struct Win32ComputerSystem {
         #[serde(rename = "UserName")]
         user_name: Option<String>,
+        #[serde(rename = "DNSHostName")]
+        dns_host_name: Option<String>,
+        #[serde(rename = "Domain")]
+        domain: Option<String>,
+        #[serde(rename = "PartOfDomain")]
+        part_of_domain: Option<bool>,
+        #[serde(rename = "Manufacturer")]
+        manufacturer: Option<String>,
+        #[serde(rename = "Model")]
+        model: Option<String>,
+        #[serde(rename = "SystemFamily")]
+        system_family: Option<String>,
+        #[serde(rename = "SystemSKUNumber")]
+        system_sku_number: Option<String>,
     }
     let cs: Vec<Win32ComputerSystem> = wmi
-        .raw_query("SELECT UserName FROM Win32_ComputerSystem")
+        .raw_query(
+            "SELECT UserName, DNSHostName, Domain, PartOfDomain, Manufacturer, Model, \
+             SystemFamily, SystemSKUNumber FROM Win32_ComputerSystem",
+        )
         .context("WMI query Win32_ComputerSystem failed")?;
-    let logged_in_user = cs.get(0).and_then(|x| x.user_name.clone());
 
-    // Laptop serial (BIOS)
+    let mut host_identity = inventory::hostname::collect(
+        root,
+        &[
+            (
+                HostnameSource::Environment,
+                std::env::var("COMPUTERNAME").ok(),
+            ),
+            (
+                HostnameSource::Wmi,
+                cs.get(0).and_then(|x| x.dns_host_name.clone()),
+            ),
+        ],
+        &ip_address,
+    );
+    // Domain holds the workgroup name on machines that are not domain members
+    if let Some(domain) = cs
+        .get(0)
+        .filter(|x| x.part_of_domain == Some(true))
+        .and_then(|x| x.domain.as_deref())
+    {
+        inventory::hostname::apply_windows_domain(&mut host_identity, domain);
+    }
+    let hostname = host_identity.hostname.clone();
+    let mut sessions =
+        inventory::best_effort("session", inventory::sessions::collect(root, Utc::now()));
+    if sessions.is_empty() {
+        sessions = wmi_sessions(&wmi);
+    }
+    // UserName is empty for RDP-only machines; fall back to the session list
+    let logged_in_user = cs
+        .get(0)
+        .and_then(|x| x.user_name.clone())
+        .or_else(|| inventory::sessions::primary_user(&sessions));
+
+    // Laptop serial and firmware identification (BIOS)
     #[derive(serde::Deserialize, Debug)]
     struct Win32Bios {
         #[serde(rename = "SerialNumber")]
         serial_number: Option<String>,
+        #[serde(rename = "Manufacturer")]
+        manufacturer: Option<String>,
+        #[serde(rename = "SMBIOSBIOSVersion")]
+        smbios_bios_version: Option<String>,
+        /// CIM datetime, e.g. `20230224000000.000000+000`
+        #[serde(rename = "ReleaseDate")]
+        release_date: Option<String>,
     }
     let bios: Vec<Win32Bios> = wmi
-        .raw_query("SELECT SerialNumber FROM Win32_BIOS")
+        .raw_query(
+            "SELECT SerialNumber, Manufacturer, SMBIOSBIOSVersion, ReleaseDate FROM Win32_BIOS",
+        )
         .context("WMI query Win32_BIOS failed")?;
-    let laptop_serial = bios
-        .get(0)
-        .and_then(|x| x.serial_number.clone())
+    let laptop_serial_raw = bios.get(0).and_then(|x| x.serial_number.clone());
+    let laptop_serial = laptop_serial_raw
+        .as_deref()
+        .and_then(|s| serial::normalize(s, &cfg.serials))
         .unwrap_or_else(|| "UNKNOWN".to_string());
 
     // Physical drives
@@ -56,18 +122,224 @@ pub fn collect() -> Result<CheckIn> {
         .into_iter()
         .map(|d| Drive {
             model: d.model.unwrap_or_else(|| "UNKNOWN".to_string()),
-            serial_number: d.serial_number.map(|s| s.trim().to_string()),
+            serial_number: d
+                .serial_number
+                .as_deref()
+                .and_then(|s| serial::normalize(s, &cfg.serials)),
             device_id: d.device_id.unwrap_or_else(|| "UNKNOWN".to_string()),
+            serial_number_raw: d.serial_number,
         })
         .collect::<Vec<_>>();
 
+    // File-based inventory sections (empty where the OS has no such source)
+    let software = inventory::best_effort(
+        "software",
+        inventory::software::collect(root, &cfg.software),
+    );
+
+    let vulnerabilities = inventory::best_effort(
+        "vulnerability",
+        osv::collect(root, &software, &cfg.vulnerabilities),
+    );
+
+    let power = inventory::best_effort("battery", inventory::battery::collect(root));
+    let monitors = inventory::best_effort("monitor", inventory::monitors::collect(root));
+    let pci_devices = inventory::best_effort("PCI", inventory::pci::collect(root, &cfg.pci));
+    let usb_devices = inventory::best_effort("USB", inventory::usb::collect(root, &cfg.usb));
+    let bluetooth_devices = inventory::best_effort(
+        "Bluetooth",
+        inventory::bluetooth::collect(root, &cfg.bluetooth),
+    );
+    let login_history = inventory::best_effort(
+        "login history",
+        inventory::login_history::collect(root, &cfg.login_history, last_checkin, Utc::now()),
+    );
+    let local_accounts = inventory::best_effort(
+        "local account",
+        inventory::accounts::collect(
+            cfg.accounts.root.as_deref().unwrap_or(root),
+            &cfg.accounts,
+            Utc::now().date_naive(),
+        ),
+    );
+    let firmware_security =
+        inventory::best_effort("firmware security", inventory::firmware::collect(root));
+    let firewall = inventory::best_effort("firewall", inventory::firewall::collect(&cfg.firewall));
+    let security_products = inventory::best_effort(
+        "security product",
+        inventory::security_products::collect(root, &cfg.security_products, &software),
+    );
+    let patch_status = inventory::best_effort(
+        "patch status",
+        inventory::patches::collect(root, Utc::now()),
+    );
+    let listening_sockets =
+        inventory::best_effort("listening socket", inventory::sockets::collect(root));
+    let processes = inventory::best_effort(
+        "process",
+        inventory::processes::collect(root, &cfg.processes, Utc::now()),
+    );
+    let services = inventory::best_effort(
+        "service",
+        inventory::services::collect(cfg.services.root.as_deref().unwrap_or(root)),
+    );
+    let cpu_security = inventory::best_effort("CPU security", inventory::cpu::collect(root));
+    let environment =
+        inventory::best_effort("environment", inventory::virtualization::collect(root));
+    // No sysfs on Windows; the same SMBIOS fields come from WMI
+    let system_identity = inventory::best_effort("system identity", inventory::dmi::collect(root))
+        .or_else(|| {
+            let (cs, bios) = (cs.first(), bios.first());
+            let partial = SystemIdentity {
+                manufacturer: cs.and_then(|x| x.manufacturer.clone()),
+                product_name: cs.and_then(|x| x.model.clone()),
+                product_version: None,
+                product_family: cs.and_then(|x| x.system_family.clone()),
+                sku: cs.and_then(|x| x.system_sku_number.clone()),
+                uuid: None,
+                board_vendor: None,
+                board_name: None,
+                bios_vendor: bios.and_then(|x| x.manufacturer.clone()),
+                bios_version: bios.and_then(|x| x.smbios_bios_version.clone()),
+                bios_release_date: bios.and_then(|x| x.release_date.clone()),
+            };
+            Some(wmi_system_identity(&wmi, partial))
+        });
+    let network = inventory::best_effort("network", inventory::network::collect(root));
+    let disk_encryption =
+        inventory::best_effort("disk encryption", inventory::encryption::collect(root));
+
+    let timestamp_utc = Utc::now().to_rfc3339();
+    let sbom = cfg.software.attach_sbom.then(|| {
+        sbom::render(
+            &software,
+            inventory::os_release::distro_id(root).as_deref(),
+            &timestamp_utc,
+        )
+    });
+
     Ok(CheckIn {
         hostname,
         ip_address,
         logged_in_user,
         laptop_serial,
         drives,
-        timestamp_utc: Utc::now().to_rfc3339(),
+        timestamp_utc,
+        software,
+        sbom,
+        vulnerabilities,
+        power,
+        monitors,
+        pci_devices,
+        usb_devices,
+        bluetooth_devices,
+        sessions,
+        login_history,
+        local_accounts,
+        disk_encryption,
+        firmware_security,
+        firewall,
+        security_products,
+        patch_status,
+        listening_sockets,
+        processes,
+        services,
+        cpu_security,
+        environment,
+        system_identity,
+        laptop_serial_raw,
+        host_identity: Some(host_identity),
+        network,
+    })
+}
+
+/// Interactive logon sessions from `Win32_LogonSession` and `Win32_LoggedOnUser`, which are
+/// queried best effort.
+fn wmi_sessions(wmi: &WMIConnection) -> Vec<Session> {
+    #[derive(serde::Deserialize, Debug)]
+    struct Win32LogonSession {
+        #[serde(rename = "LogonId")]
+        logon_id: String,
+        #[serde(rename = "LogonType")]
+        logon_type: u32,
+        #[serde(rename = "StartTime")]
+        start_time: Option<String>,
+    }
+    #[derive(serde::Deserialize, Debug)]
+    struct Win32LoggedOnUser {
+        #[serde(rename = "Antecedent")]
+        antecedent: String,
+        #[serde(rename = "Dependent")]
+        dependent: String,
+    }
+    let logons: Vec<Win32LogonSession> = inventory::best_effort(
+        "session",
+        wmi.raw_query(
+            "SELECT LogonId, LogonType, StartTime FROM Win32_LogonSession \
+             WHERE LogonType = 2 OR LogonType = 10 OR LogonType = 11 OR LogonType = 12",
+        )
+        .context("WMI query Win32_LogonSession failed"),
+    );
+    if logons.is_empty() {
+        return Vec::new();
+    }
+    let logged_on: Vec<Win32LoggedOnUser> = inventory::best_effort(
+        "session",
+        wmi.raw_query("SELECT Antecedent, Dependent FROM Win32_LoggedOnUser")
+            .context("WMI query Win32_LoggedOnUser failed"),
+    );
+
+    let logons: Vec<_> = logons
+        .into_iter()
+        .map(|x| inventory::sessions::WindowsLogon {
+            logon_id: x.logon_id,
+            logon_type: x.logon_type,
+            start_time: x.start_time,
+        })
+        .collect();
+    let logged_on: Vec<_> = logged_on
+        .into_iter()
+        .map(|x| (x.antecedent, x.dependent))
+        .collect();
+    inventory::sessions::from_windows_logons(&logons, &logged_on)
+}
+
+/// Complete the SMBIOS identification read from `Win32_ComputerSystem` and `Win32_BIOS` with
+/// `Win32_ComputerSystemProduct` and `Win32_BaseBoard`, which are queried best effort.
+fn wmi_system_identity(wmi: &WMIConnection, partial: SystemIdentity) -> SystemIdentity {
+    #[derive(serde::Deserialize, Debug)]
+    struct Win32ComputerSystemProduct {
+        #[serde(rename = "UUID")]
+        uuid: Option<String>,
+        #[serde(rename = "Version")]
+        version: Option<String>,
+    }
+    #[derive(serde::Deserialize, Debug)]
+    struct Win32BaseBoard {
+        #[serde(rename = "Manufacturer")]
+        manufacturer: Option<String>,
+        #[serde(rename = "Product")]
+        product: Option<String>,
+    }
+    let product: Vec<Win32ComputerSystemProduct> = inventory::best_effort(
+        "system identity",
+        wmi.raw_query("SELECT UUID, Version FROM Win32_ComputerSystemProduct")
+            .context("WMI query Win32_ComputerSystemProduct failed"),
+    );
+    let board: Vec<Win32BaseBoard> = inventory::best_effort(
+        "system identity",
+        wmi.raw_query("SELECT Manufacturer, Product FROM Win32_BaseBoard")
+            .context("WMI query Win32_BaseBoard failed"),
+    );
+    let product = product.into_iter().next();
+    let board = board.into_iter().next();
+
+    inventory::dmi::normalize(SystemIdentity {
+        product_version: product.as_ref().and_then(|x| x.version.clone()),
+        uuid: product.and_then(|x| x.uuid),
+        board_vendor: board.as_ref().and_then(|x| x.manufacturer.clone()),
+        board_name: board.and_then(|x| x.product),
+        ..partial
     })
 }
 
@@ -13,18 +13,418 @@ pub struct Config {
 
     #[serde(default)]
     pub tls_insecure: bool,
+
+    #[serde(default)]
+    pub software: SoftwareConfig,
+
+    #[serde(default)]
+    pub vulnerabilities: VulnerabilityConfig,
+
+    #[serde(default)]
+    pub pci: PciConfig,
+
+    #[serde(default)]
+    pub usb: UsbConfig,
+
+    #[serde(default)]
+    pub bluetooth: BluetoothConfig,
+
+    #[serde(default)]
+    pub login_history: LoginHistoryConfig,
+
+    #[serde(default)]
+    pub accounts: AccountsConfig,
+
+    #[serde(default)]
+    pub firewall: FirewallConfig,
+
+    /// Security product catalog (`[[security_products]]` tables). Replaces the built-in
+    /// catalog when present.
+    #[serde(default = "default_security_products")]
+    pub security_products: Vec<SecurityProductRule>,
+
+    #[serde(default)]
+    pub processes: ProcessConfig,
+
+    #[serde(default)]
+    pub services: ServicesConfig,
+
+    #[serde(default)]
+    pub serials: SerialConfig,
+}
+
+/// Installed software collection settings (`[software]` table).
+#[derive(Debug, Deserialize, Clone)]
+pub struct SoftwareConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Skip the dpkg status file if it is larger than this many bytes.
+    #[serde(default = "default_max_dpkg_status_bytes")]
+    pub max_dpkg_status_bytes: u64,
+
+    /// Skip the RPM database if it is larger than this many bytes.
+    #[serde(default = "default_max_rpmdb_bytes")]
+    pub max_rpmdb_bytes: u64,
+
+    /// Attach a CycloneDX SBOM of the installed software to each check-in.
+    #[serde(default)]
+    pub attach_sbom: bool,
+}
+
+/// Offline OSV vulnerability matching settings (`[vulnerabilities]` table).
+#[derive(Debug, Deserialize, Clone)]
+pub struct VulnerabilityConfig {
+    /// Path to an OSV database export (zip of JSON advisories). Matching is off when unset.
+    #[serde(default)]
+    pub osv_database: Option<PathBuf>,
+
+    /// OSV ecosystem such as `Debian:12`; detected from os-release when unset.
+    #[serde(default)]
+    pub ecosystem: Option<String>,
+
+    /// Skip advisories whose uncompressed JSON is larger than this many bytes.
+    #[serde(default = "default_max_advisory_bytes")]
+    pub max_advisory_bytes: u64,
+}
+
+/// PCI device inventory settings (`[pci]` table).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PciConfig {
+    /// `pci.ids` file used for device names. When unset, a `pci.ids` next to the executable is
+    /// used, then the system copy.
+    #[serde(default)]
+    pub pci_ids: Option<PathBuf>,
+}
+
+/// USB device inventory settings (`[usb]` table).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct UsbConfig {
+    /// Report hubs, including the controllers' root hubs.
+    #[serde(default)]
+    pub include_hubs: bool,
+
+    /// `vendor:product` hex IDs to drop; either half may be `*` (e.g. `"8087:*"`).
+    #[serde(default)]
+    pub exclude_ids: Vec<String>,
+
+    /// Two-digit hex USB class codes to drop (e.g. `"e0"` for internal Bluetooth radios).
+    #[serde(default)]
+    pub exclude_classes: Vec<String>,
+}
+
+/// Paired Bluetooth device settings (`[bluetooth]` table).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct BluetoothConfig {
+    /// Report device addresses as HMAC-SHA256 hashes instead of plain addresses.
+    #[serde(default)]
+    pub hash_addresses: bool,
+
+    /// Secret key for address hashing. Without one, hashes can be reversed by enumerating
+    /// addresses from the vendor's OUI range.
+    #[serde(default)]
+    pub hash_key: Option<String>,
+}
+
+/// Login history settings (`[login_history]` table).
+#[derive(Debug, Deserialize, Clone)]
+pub struct LoginHistoryConfig {
+    /// Most recent events to send per check-in; older ones are dropped.
+    #[serde(default = "default_max_login_events")]
+    pub max_events: usize,
+
+    /// Window in days for the most frequent user summary. Also bounds the history sent on the
+    /// first check-in after the agent starts.
+    #[serde(default = "default_frequent_user_days")]
+    pub frequent_user_days: u32,
+}
+
+/// Local account audit settings (`[accounts]` table).
+#[derive(Debug, Deserialize, Clone)]
+pub struct AccountsConfig {
+    /// Directory containing `etc/passwd`, `etc/shadow` and `etc/group`; the system root when
+    /// unset.
+    #[serde(default)]
+    pub root: Option<PathBuf>,
+
+    /// Groups whose members are reported as privileged.
+    #[serde(default = "default_privileged_groups")]
+    pub privileged_groups: Vec<String>,
+}
+
+/// Host firewall settings (`[firewall]` table).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FirewallConfig {
+    /// Text expected in a table name or rule comment of rulesets deployed by configuration
+    /// management, e.g. "managed-by-ansible".
+    #[serde(default)]
+    pub managed_marker: Option<String>,
+}
+
+/// How to recognize one AV/EDR product. A product is installed if any package, service unit
+/// or path matches, and running if any process or service is.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct SecurityProductRule {
+    pub name: String,
+
+    /// Free-form category, e.g. "edr" or "antivirus".
+    #[serde(default)]
+    pub category: Option<String>,
+
+    /// Package names; the first installed one provides the version.
+    #[serde(default)]
+    pub packages: Vec<String>,
+
+    /// systemd unit names, e.g. "falcon-sensor.service".
+    #[serde(default)]
+    pub services: Vec<String>,
+
+    /// Process names as shown by `ps -e`.
+    #[serde(default)]
+    pub processes: Vec<String>,
+
+    /// Absolute paths of files or directories the product installs.
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+}
+
+/// Process snapshot settings (`[processes]` table).
+#[derive(Debug, Deserialize, Clone)]
+pub struct ProcessConfig {
+    /// Include a snapshot of running processes in each check-in.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Most processes to send; flagged processes are kept first, then the largest by memory.
+    #[serde(default = "default_max_processes")]
+    pub max_processes: usize,
+
+    /// Executables to flag. Patterns with a `/` match the executable path, others the process
+    /// name; `*` matches any run of characters.
+    #[serde(default)]
+    pub deny_executables: Vec<String>,
+
+    /// Executables never flagged, even when they match `deny_executables`. Same pattern syntax.
+    #[serde(default)]
+    pub allow_executables: Vec<String>,
+}
+
+/// Service and scheduled job settings (`[services]` table).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ServicesConfig {
+    /// Directory containing `etc/systemd`, `etc/crontab` and `var/spool/cron`; the system root
+    /// when unset.
+    #[serde(default)]
+    pub root: Option<PathBuf>,
+}
+
+/// Serial number validation settings (`[serials]` table).
+#[derive(Debug, Deserialize, Clone)]
+pub struct SerialConfig {
+    /// Values reported as absent, compared case-insensitively; `*` matches any run of
+    /// characters. Empty and all-zero serials are always rejected.
+    #[serde(default = "default_serial_placeholders")]
+    pub placeholders: Vec<String>,
 }
 
 fn default_interval() -> u64 {
     1800
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_dpkg_status_bytes() -> u64 {
+    16 * 1024 * 1024
+}
+
+fn default_max_rpmdb_bytes() -> u64 {
+    256 * 1024 * 1024
+}
+
+fn default_max_advisory_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_max_login_events() -> usize {
+    200
+}
+
+fn default_frequent_user_days() -> u32 {
+    30
+}
+
+fn default_max_processes() -> usize {
+    300
+}
+
+fn default_privileged_groups() -> Vec<String> {
+    ["sudo", "wheel", "admin", "root"]
+        .iter()
+        .map(|g| g.to_string())
+        .collect()
+}
+
+fn default_serial_placeholders() -> Vec<String> {
+    [
+        "To be filled by O.E.M.",
+        "Default string",
+        "System Serial Number",
+        "Chassis Serial Number",
+        "Base Board Serial Number",
+        "Not Specified",
+        "Not Applicable",
+        "None",
+        "N/A",
+        "Unknown",
+        "Invalid",
+        "0123456789",
+        "123456789",
+    ]
+    .iter()
+    .map(|p| p.to_string())
+    .collect()
+}
+
+fn default_security_products() -> Vec<SecurityProductRule> {
+    let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+    let rule = |name: &str,
+                category: &str,
+                packages: &[&str],
+                service: &str,
+                process: &str,
+                path: &str| {
+        SecurityProductRule {
+            name: name.to_string(),
+            category: Some(category.to_string()),
+            packages: strings(packages),
+            services: vec![service.to_string()],
+            processes: vec![process.to_string()],
+            paths: vec![PathBuf::from(path)],
+        }
+    };
+    vec![
+        rule(
+            "CrowdStrike Falcon",
+            "edr",
+            &["falcon-sensor"],
+            "falcon-sensor.service",
+            "falcon-sensor",
+            "/opt/CrowdStrike",
+        ),
+        rule(
+            "Microsoft Defender for Endpoint",
+            "edr",
+            &["mdatp"],
+            "mdatp.service",
+            "wdavdaemon",
+            "/opt/microsoft/mdatp",
+        ),
+        rule(
+            "SentinelOne",
+            "edr",
+            &["sentinelagent", "SentinelAgent"],
+            "sentinelone.service",
+            "s1-agent",
+            "/opt/sentinelone",
+        ),
+        // Installed by Sophos' own installer, not as a package
+        rule(
+            "Sophos Protection",
+            "antivirus",
+            &[],
+            "sophos-spl.service",
+            "sophos_managementagent",
+            "/opt/sophos-spl",
+        ),
+        rule(
+            "ClamAV",
+            "antivirus",
+            &["clamav-daemon", "clamd"],
+            "clamav-daemon.service",
+            "clamd",
+            "/usr/sbin/clamd",
+        ),
+    ]
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             api_url: None,
             interval_seconds: default_interval(),
             tls_insecure: false,
+            software: SoftwareConfig::default(),
+            vulnerabilities: VulnerabilityConfig::default(),
+            pci: PciConfig::default(),
+            usb: UsbConfig::default(),
+            bluetooth: BluetoothConfig::default(),
+            login_history: LoginHistoryConfig::default(),
+            accounts: AccountsConfig::default(),
+            firewall: FirewallConfig::default(),
+            security_products: default_security_products(),
+            processes: ProcessConfig::default(),
+            services: ServicesConfig::default(),
+            serials: SerialConfig::default(),
+        }
+    }
+}
+
+impl Default for SoftwareConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_dpkg_status_bytes: default_max_dpkg_status_bytes(),
+            max_rpmdb_bytes: default_max_rpmdb_bytes(),
+            attach_sbom: false,
+        }
+    }
+}
+
+impl Default for LoginHistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_events: default_max_login_events(),
+            frequent_user_days: default_frequent_user_days(),
+        }
+    }
+}
+
+impl Default for AccountsConfig {
+    fn default() -> Self {
+        Self {
+            root: None,
+            privileged_groups: default_privileged_groups(),
+        }
+    }
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_processes: default_max_processes(),
+            deny_executables: Vec::new(),
+            allow_executables: Vec::new(),
+        }
+    }
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self {
+            placeholders: default_serial_placeholders(),
+        }
+    }
+}
+
+impl Default for VulnerabilityConfig {
+    fn default() -> Self {
+        Self {
+            osv_database: None,
+            ecosystem: None,
+            max_advisory_bytes: default_max_advisory_bytes(),
         }
     }
 }
@@ -57,12 +457,90 @@ interval_seconds = 1800
 
 # Accept invalid TLS certificates (LAB USE ONLY - do not enable in production)
 tls_insecure = false
+
+# Installed software inventory (dpkg status file and RPM database)
+[software]
+enabled = true
+# Sources larger than these limits are skipped
+max_dpkg_status_bytes = 16777216
+max_rpmdb_bytes = 268435456
+# Also send the software list as a CycloneDX SBOM in the check-in
+attach_sbom = false
+
+# Offline vulnerability matching against a local OSV database export
+[vulnerabilities]
+# osv_database = "C:\\ProgramData\\InventoryAgent\\osv\\all.zip"
+# ecosystem = "Debian:12"   # detected from /etc/os-release when unset
+max_advisory_bytes = 1048576
+
+# PCI device names come from pci.ids: this path, else pci.ids next to the
+# executable, else the system copy (/usr/share/hwdata/pci.ids and similar)
+[pci]
+# pci_ids = "C:\\Program Files\\InventoryAgent\\pci.ids"
+
+# USB devices; hubs are skipped unless include_hubs is set
+[usb]
+include_hubs = false
+exclude_ids = []        # "vendor:product" hex, e.g. "8087:*"
+exclude_classes = []    # hex class codes, e.g. "e0" (Bluetooth radios)
+
+# Paired Bluetooth devices; addresses can be sent as keyed hashes instead
+[bluetooth]
+hash_addresses = false
+# hash_key = "change-me"
+
+# Logins, logouts, reboots and shutdowns since the previous check-in (wtmp)
+[login_history]
+max_events = 200
+frequent_user_days = 30
+
+# Local accounts and members of privileged groups (passwd, shadow, group)
+[accounts]
+# root = "/mnt/image"   # read account files from another root
+privileged_groups = ["sudo", "wheel", "admin", "root"]
+
+# Host firewall state (nft, else iptables-save and ip6tables-save)
+[firewall]
+# managed_marker = "managed-by-ansible"   # expected in a table name or rule comment
+
+# AV/EDR products to look for. Any [[security_products]] table replaces the
+# built-in catalog (CrowdStrike Falcon, Microsoft Defender for Endpoint,
+# SentinelOne, Sophos, ClamAV).
+# [[security_products]]
+# name = "CrowdStrike Falcon"
+# category = "edr"
+# packages = ["falcon-sensor"]
+# services = ["falcon-sensor.service"]
+# processes = ["falcon-sensor"]
+# paths = ["/opt/CrowdStrike"]
+
+# Running process snapshot; deny_executables flags matching processes unless
+# allow_executables also matches them
+[processes]
+enabled = false
+max_processes = 300
+deny_executables = []    # e.g. "xmrig", "/tmp/*"
+allow_executables = []   # e.g. "/tmp/.mount_*/AppRun"
+
+# systemd services, cron tables and systemd timers
+[services]
+# root = "/mnt/image"   # read unit files and crontabs from another root
+
+# Laptop and drive serials matching these are reported as null (case-insensitive,
+# "*" wildcard). Empty and all-zero serials are always rejected.
+[serials]
+placeholders = [
+    "To be filled by O.E.M.", "Default string", "System Serial Number",
+    "Chassis Serial Number", "Base Board Serial Number", "Not Specified",
+    "Not Applicable", "None", "N/A", "Unknown", "Invalid", "0123456789", "123456789",
+]
 "#;
 
     std::fs::write(config_path, template)
         .with_context(|| format!("failed to write template config to {}", config_path.display()))?;
 
-    println!("Generated template config file: {}", config_path.display());
+    // stderr keeps stdout clean for `--sbom` output
+    eprintln!("Generated template config file: {}", config_path.display());
     Ok(())
 }
 
@@ -104,6 +582,7 @@ pub fn load_config() -> Result<Config> {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -153,6 +632,181 @@ mod tests {
         assert_eq!(config.tls_insecure, false);
     }
 
+    #[test]
+    fn test_toml_parse_software_section() {
+        let toml = r#"
+            [software]
+            max_dpkg_status_bytes = 1024
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.software.enabled);
+        assert_eq!(config.software.max_dpkg_status_bytes, 1024);
+        assert_eq!(config.software.max_rpmdb_bytes, default_max_rpmdb_bytes());
+        assert!(!config.software.attach_sbom);
+    }
+
+    #[test]
+    fn test_toml_parse_vulnerabilities_section() {
+        let toml = r#"
+            [vulnerabilities]
+            osv_database = "/var/lib/osv/Debian.zip"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.vulnerabilities.osv_database,
+            Some(PathBuf::from("/var/lib/osv/Debian.zip"))
+        );
+        assert_eq!(config.vulnerabilities.ecosystem, None);
+        assert_eq!(config.vulnerabilities.max_advisory_bytes, 1024 * 1024);
+    }
+
+    #[test]
+    fn test_toml_parse_pci_section() {
+        let toml = r#"
+            [pci]
+            pci_ids = "/opt/inventory-agent/pci.ids"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.pci.pci_ids,
+            Some(PathBuf::from("/opt/inventory-agent/pci.ids"))
+        );
+        assert_eq!(Config::default().pci.pci_ids, None);
+    }
+
+    #[test]
+    fn test_toml_parse_usb_section() {
+        let toml = r#"
+            [usb]
+            exclude_ids = ["8087:*", "06cb:00bd"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(!config.usb.include_hubs);
+        assert_eq!(config.usb.exclude_ids, vec!["8087:*", "06cb:00bd"]);
+        assert!(config.usb.exclude_classes.is_empty());
+    }
+
+    #[test]
+    fn test_toml_parse_bluetooth_section() {
+        let toml = r#"
+            [bluetooth]
+            hash_addresses = true
+            hash_key = "fleet-secret"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.bluetooth.hash_addresses);
+        assert_eq!(config.bluetooth.hash_key.as_deref(), Some("fleet-secret"));
+        assert!(!Config::default().bluetooth.hash_addresses);
+    }
+
+    #[test]
+    fn test_toml_parse_login_history_section() {
+        let toml = r#"
+            [login_history]
+            frequent_user_days = 14
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.login_history.max_events, 200);
+        assert_eq!(config.login_history.frequent_user_days, 14);
+    }
+
+    #[test]
+    fn test_toml_parse_accounts_section() {
+        let toml = r#"
+            [accounts]
+            privileged_groups = ["wheel", "docker"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.accounts.root, None);
+        assert_eq!(config.accounts.privileged_groups, vec!["wheel", "docker"]);
+        assert_eq!(
+            Config::default().accounts.privileged_groups,
+            vec!["sudo", "wheel", "admin", "root"]
+        );
+    }
+
+    #[test]
+    fn test_toml_parse_firewall_section() {
+        let toml = r#"
+            [firewall]
+            managed_marker = "managed-by-ansible"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.firewall.managed_marker.as_deref(),
+            Some("managed-by-ansible")
+        );
+        assert_eq!(Config::default().firewall.managed_marker, None);
+    }
+
+    #[test]
+    fn test_toml_parse_security_products() {
+        let toml = r#"
+            [[security_products]]
+            name = "Acme EDR"
+            processes = ["acmed"]
+            paths = ["/opt/acme"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.security_products.len(), 1);
+        let rule = &config.security_products[0];
+        assert_eq!(rule.name, "Acme EDR");
+        assert_eq!(rule.category, None);
+        assert!(rule.packages.is_empty());
+        assert_eq!(rule.paths, vec![PathBuf::from("/opt/acme")]);
+
+        // Without the table the built-in catalog applies
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config
+            .security_products
+            .iter()
+            .any(|p| p.name == "CrowdStrike Falcon"));
+    }
+
+    #[test]
+    fn test_toml_parse_processes_section() {
+        let toml = r#"
+            [processes]
+            enabled = true
+            deny_executables = ["xmrig", "/tmp/*"]
+            allow_executables = ["/tmp/.mount_*/AppRun"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.processes.enabled);
+        assert_eq!(config.processes.max_processes, 300);
+        assert_eq!(config.processes.deny_executables, vec!["xmrig", "/tmp/*"]);
+        assert_eq!(
+            config.processes.allow_executables,
+            vec!["/tmp/.mount_*/AppRun"]
+        );
+        assert!(!Config::default().processes.enabled);
+    }
+
+    #[test]
+    fn test_toml_parse_services_section() {
+        let toml = r#"
+            [services]
+            root = "/mnt/image"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.services.root, Some(PathBuf::from("/mnt/image")));
+        assert_eq!(Config::default().services.root, None);
+    }
+
+    #[test]
+    fn test_toml_parse_serials_section() {
+        let toml = r#"
+            [serials]
+            placeholders = ["SN-TEMPLATE*"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.serials.placeholders, vec!["SN-TEMPLATE*"]);
+        assert!(Config::default()
+            .serials
+            .placeholders
+            .contains(&"To be filled by O.E.M.".to_string()));
+    }
+
     #[test]
     fn test_exe_dir_success() {
         let result = exe_dir();
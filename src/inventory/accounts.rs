@@ -0,0 +1,293 @@
+//! Local accounts and privileged group membership from `/etc/passwd`, `/etc/shadow` and
+//! `/etc/group`.
+//!
+//! Only shadow metadata is read; password hashes never leave this module.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate};
+
+use crate::config::AccountsConfig;
+use crate::models::{LocalAccount, LocalAccounts, PrivilegedGroup};
+
+/// First UID of regular users when `login.defs` does not say otherwise.
+const DEFAULT_UID_MIN: u32 = 1000;
+/// Last UID of regular users when `login.defs` does not say otherwise.
+const DEFAULT_UID_MAX: u32 = 60000;
+
+/// Shells that refuse interactive logins.
+const NOLOGIN_SHELLS: &[&str] = &["nologin", "false"];
+
+struct ShadowEntry {
+    name: String,
+    locked: bool,
+    last_change: Option<NaiveDate>,
+    expired: bool,
+}
+
+struct GroupEntry {
+    name: String,
+    gid: u32,
+    members: Vec<String>,
+}
+
+/// Read accounts and privileged groups. Returns `None` if there is no passwd file.
+///
+/// `today` decides whether an account expiry date has passed.
+pub fn collect(
+    root: &Path,
+    cfg: &AccountsConfig,
+    today: NaiveDate,
+) -> Result<Option<LocalAccounts>> {
+    let Some(passwd) = read_optional(&root.join("etc/passwd"))? else {
+        return Ok(None);
+    };
+    // shadow is root-only; without it lock state and password age are unknown
+    let shadow = read_optional(&root.join("etc/shadow"))
+        .unwrap_or_default()
+        .map(|s| parse_shadow(&s, today));
+    let groups = read_optional(&root.join("etc/group"))?
+        .map(|g| parse_group(&g))
+        .unwrap_or_default();
+    let login_defs = read_optional(&root.join("etc/login.defs"))
+        .unwrap_or_default()
+        .unwrap_or_default();
+    let uid_min = login_defs_value(&login_defs, "UID_MIN").unwrap_or(DEFAULT_UID_MIN);
+    // Service accounts such as nobody (65534) sit above the regular range
+    let uid_max = login_defs_value(&login_defs, "UID_MAX").unwrap_or(DEFAULT_UID_MAX);
+
+    let mut users = Vec::new();
+    for line in passwd.lines() {
+        // name:password:uid:gid:gecos:home:shell
+        let fields: Vec<&str> = line.split(':').collect();
+        if fields.len() < 7 || fields[0].starts_with('+') || fields[0].starts_with('-') {
+            continue;
+        }
+        let (Ok(uid), Ok(gid)) = (fields[2].parse::<u32>(), fields[3].parse::<u32>()) else {
+            continue;
+        };
+        let name = fields[0].to_string();
+        let shell = fields[6].to_string();
+        let shadow_entry = shadow
+            .as_ref()
+            .and_then(|entries| entries.iter().find(|e| e.name == name));
+        let nologin = NOLOGIN_SHELLS
+            .iter()
+            .any(|s| Path::new(&shell).file_name().and_then(|f| f.to_str()) == Some(s));
+
+        users.push(LocalAccount {
+            uid,
+            gid,
+            full_name: fields[4]
+                .split(',')
+                .next()
+                .filter(|g| !g.is_empty())
+                .map(str::to_string),
+            home: fields[5].to_string(),
+            disabled: nologin || shadow_entry.is_some_and(|e| e.expired),
+            locked: shadow_entry.map(|e| e.locked),
+            last_password_change: shadow_entry
+                .and_then(|e| e.last_change)
+                .map(|d| d.format("%Y-%m-%d").to_string()),
+            system_account: uid != 0 && !(uid_min..=uid_max).contains(&uid),
+            groups: groups
+                .iter()
+                .filter(|g| g.gid == gid || g.members.contains(&name))
+                .map(|g| g.name.clone())
+                .collect(),
+            shell,
+            name,
+        });
+    }
+
+    let privileged_groups = cfg
+        .privileged_groups
+        .iter()
+        .filter_map(|wanted| groups.iter().find(|g| &g.name == wanted))
+        .map(|group| {
+            // Members listed in the group plus users whose primary group it is
+            let mut members = group.members.clone();
+            for user in users.iter().filter(|u| u.gid == group.gid) {
+                if !members.contains(&user.name) {
+                    members.push(user.name.clone());
+                }
+            }
+            PrivilegedGroup {
+                name: group.name.clone(),
+                gid: group.gid,
+                members,
+            }
+        })
+        .collect();
+
+    Ok(Some(LocalAccounts {
+        users,
+        privileged_groups,
+    }))
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Parse shadow lines (`name:password:lastchg:min:max:warn:inactive:expire:`), keeping only
+/// metadata.
+fn parse_shadow(contents: &str, today: NaiveDate) -> Vec<ShadowEntry> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    let day = |field: Option<&str>| -> Option<NaiveDate> {
+        let days: i64 = field?.parse().ok()?;
+        epoch.checked_add_signed(Duration::days(days))
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next().filter(|n| !n.is_empty())?;
+            let password = fields.next().unwrap_or("");
+            let last_change = fields.next();
+            let expire = fields.nth(4);
+            Some(ShadowEntry {
+                name: name.to_string(),
+                // "!" prefixes a hash disabled with `passwd -l`; "*" means no password was set
+                locked: password.starts_with('!') || password.starts_with('*'),
+                // A last change of 0 means the password must be changed at next login
+                last_change: day(last_change).filter(|d| *d != epoch),
+                expired: day(expire).is_some_and(|d| d <= today),
+            })
+        })
+        .collect()
+}
+
+fn parse_group(contents: &str) -> Vec<GroupEntry> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            // name:password:gid:member,member
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 4 {
+                return None;
+            }
+            Some(GroupEntry {
+                name: fields[0].to_string(),
+                gid: fields[2].parse().ok()?,
+                members: fields[3]
+                    .split(',')
+                    .filter(|m| !m.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn login_defs_value(contents: &str, key: &str) -> Option<u32> {
+    contents.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        (parts.next()? == key).then(|| parts.next()?.parse().ok())?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/accounts")
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, 9).unwrap()
+    }
+
+    fn user<'a>(accounts: &'a LocalAccounts, name: &str) -> &'a LocalAccount {
+        accounts.users.iter().find(|u| u.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_collect_accounts() {
+        let accounts = collect(&fixture_root(), &AccountsConfig::default(), today())
+            .unwrap()
+            .unwrap();
+        assert_eq!(accounts.users.len(), 7);
+
+        let root = user(&accounts, "root");
+        assert!(!root.system_account);
+        assert_eq!(root.locked, Some(true));
+
+        let daemon = user(&accounts, "daemon");
+        assert!(daemon.system_account && daemon.disabled);
+        let nobody = user(&accounts, "nobody");
+        assert!(nobody.system_account && nobody.disabled);
+
+        let alice = user(&accounts, "alice");
+        assert_eq!(alice.uid, 1000);
+        assert_eq!(alice.full_name.as_deref(), Some("Alice Example"));
+        assert_eq!(alice.shell, "/bin/bash");
+        assert!(!alice.disabled);
+        assert_eq!(alice.locked, Some(false));
+        assert_eq!(alice.last_password_change.as_deref(), Some("2025-06-02"));
+        assert_eq!(alice.groups, vec!["sudo", "alice"]);
+
+        // Locked with `passwd -l`
+        assert_eq!(user(&accounts, "bob").locked, Some(true));
+        // Expired account
+        let carol = user(&accounts, "carol");
+        assert!(carol.disabled);
+        // Forced password change (lastchg 0)
+        assert_eq!(carol.last_password_change, None);
+    }
+
+    #[test]
+    fn test_privileged_groups() {
+        let accounts = collect(&fixture_root(), &AccountsConfig::default(), today())
+            .unwrap()
+            .unwrap();
+        let groups: Vec<_> = accounts
+            .privileged_groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.members.clone()))
+            .collect();
+        // There is no wheel group in the fixture; root is a member of root by primary group
+        assert_eq!(
+            groups,
+            vec![
+                ("sudo", vec!["alice".to_string(), "svc-deploy".to_string()]),
+                ("root", vec!["root".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_shadow_access() {
+        let root = std::env::temp_dir().join("inventory-agent-accounts-test");
+        std::fs::create_dir_all(root.join("etc")).unwrap();
+        std::fs::write(
+            root.join("etc/passwd"),
+            "alice:x:1000:1000:Alice:/home/alice:/bin/bash\n",
+        )
+        .unwrap();
+        let accounts = collect(&root, &AccountsConfig::default(), today())
+            .unwrap()
+            .unwrap();
+        assert_eq!(accounts.users[0].locked, None);
+        assert_eq!(accounts.users[0].last_password_change, None);
+        assert!(accounts.privileged_groups.is_empty());
+    }
+
+    #[test]
+    fn test_missing_passwd() {
+        let accounts = collect(
+            Path::new("/nonexistent"),
+            &AccountsConfig::default(),
+            today(),
+        );
+        assert!(accounts.unwrap().is_none());
+    }
+}
@@ -0,0 +1,156 @@
+//! Battery inventory and health from `/sys/class/power_supply`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::read_attr;
+use crate::models::{Battery, CapacityUnit, PowerStatus};
+
+const POWER_SUPPLY: &str = "sys/class/power_supply";
+
+/// Read batteries and AC adapter state. Returns `None` if the machine reports no power supplies.
+pub fn collect(root: &Path) -> Result<Option<PowerStatus>> {
+    let dir = root.join(POWER_SUPPLY);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut entries = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to list {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let mut batteries = Vec::new();
+    let mut ac_online = None;
+
+    for path in entries {
+        match read_attr(&path, "type").as_deref() {
+            Some("Battery") => {
+                // Peripheral batteries (mice, headsets) report scope "Device"
+                if read_attr(&path, "scope").as_deref() == Some("Device") {
+                    continue;
+                }
+                batteries.push(read_battery(&path));
+            }
+            Some("Mains") | Some("USB") => {
+                if let Some(online) = read_attr(&path, "online") {
+                    // Any online adapter means the machine is on AC
+                    ac_online = Some(ac_online.unwrap_or(false) || online == "1");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if batteries.is_empty() && ac_online.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(PowerStatus {
+        ac_online,
+        batteries,
+    }))
+}
+
+fn read_battery(path: &Path) -> Battery {
+    // Drivers report either energy (µWh) or charge (µAh) counters
+    let (unit, design, full) = match (
+        read_number(path, "energy_full_design"),
+        read_number(path, "energy_full"),
+    ) {
+        (None, None) => (
+            CapacityUnit::MilliampHours,
+            read_number(path, "charge_full_design"),
+            read_number(path, "charge_full"),
+        ),
+        (design, full) => (CapacityUnit::MilliwattHours, design, full),
+    };
+    let design = design.map(|v| v / 1000);
+    let full = full.map(|v| v / 1000);
+
+    let health_percent = match (design, full) {
+        (Some(d), Some(f)) if d > 0 => Some((f as f64 * 1000.0 / d as f64).round() / 10.0),
+        _ => None,
+    };
+
+    Battery {
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        manufacturer: read_attr(path, "manufacturer"),
+        model: read_attr(path, "model_name"),
+        serial_number: read_attr(path, "serial_number"),
+        technology: read_attr(path, "technology"),
+        status: read_attr(path, "status"),
+        capacity_unit: unit,
+        design_capacity: design,
+        full_charge_capacity: full,
+        cycle_count: read_number(path, "cycle_count"),
+        health_percent,
+    }
+}
+
+fn read_number(dir: &Path, name: &str) -> Option<u64> {
+    read_attr(dir, name)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/battery")
+            .join(name)
+    }
+
+    #[test]
+    fn test_energy_reporting_laptop() {
+        let power = collect(&fixture_root("energy")).unwrap().unwrap();
+        assert_eq!(power.ac_online, Some(true));
+        assert_eq!(power.batteries.len(), 1, "peripheral battery is skipped");
+
+        let bat = &power.batteries[0];
+        assert_eq!(bat.name, "BAT0");
+        assert_eq!(bat.manufacturer.as_deref(), Some("SMP"));
+        assert_eq!(bat.model.as_deref(), Some("5B10W13930"));
+        assert_eq!(bat.serial_number.as_deref(), Some("1234"));
+        assert_eq!(bat.capacity_unit, CapacityUnit::MilliwattHours);
+        assert_eq!(bat.design_capacity, Some(57000));
+        assert_eq!(bat.full_charge_capacity, Some(48450));
+        assert_eq!(bat.cycle_count, Some(312));
+        assert_eq!(bat.health_percent, Some(85.0));
+        assert_eq!(bat.status.as_deref(), Some("Charging"));
+    }
+
+    #[test]
+    fn test_charge_reporting_laptop() {
+        let power = collect(&fixture_root("charge")).unwrap().unwrap();
+        assert_eq!(power.ac_online, Some(false));
+
+        let bat = &power.batteries[0];
+        assert_eq!(bat.capacity_unit, CapacityUnit::MilliampHours);
+        assert_eq!(bat.design_capacity, Some(4000));
+        assert_eq!(bat.full_charge_capacity, Some(3627));
+        assert_eq!(bat.health_percent, Some(90.7));
+        // Empty serial attribute is treated as absent
+        assert_eq!(bat.serial_number, None);
+        assert_eq!(bat.cycle_count, None);
+    }
+
+    #[test]
+    fn test_desktop_without_batteries() {
+        let power = collect(&fixture_root("desktop")).unwrap().unwrap();
+        assert_eq!(power.ac_online, Some(true));
+        assert!(power.batteries.is_empty());
+    }
+
+    #[test]
+    fn test_no_power_supply_class() {
+        assert!(collect(&fixture_root("missing")).unwrap().is_none());
+    }
+}
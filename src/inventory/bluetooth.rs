@@ -0,0 +1,346 @@
+//! Paired Bluetooth devices from the BlueZ storage directory (`/var/lib/bluetooth`).
+//!
+//! BlueZ keeps one directory per adapter, named by its address, holding one directory per known
+//! device with an `info` key file. Bonded devices have a link key section in that file.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::config::BluetoothConfig;
+use crate::models::BluetoothDevice;
+
+const BLUEZ_STORAGE: &str = "var/lib/bluetooth";
+
+/// `info` sections that hold a bonding key (BR/EDR link key or LE long term keys).
+const KEY_SECTIONS: &[&str] = &[
+    "LinkKey",
+    "LongTermKey",
+    "PeripheralLongTermKey",
+    "SlaveLongTermKey",
+];
+
+/// Parsed key file: section -> key -> value.
+type KeyFile = HashMap<String, HashMap<String, String>>;
+
+/// List devices that are paired or trusted on any adapter.
+pub fn collect(root: &Path, cfg: &BluetoothConfig) -> Result<Vec<BluetoothDevice>> {
+    let dir = root.join(BLUEZ_STORAGE);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut devices = Vec::new();
+    for adapter in list_address_dirs(&dir)? {
+        for device in list_address_dirs(&adapter)? {
+            let info = device.join("info");
+            let contents = match std::fs::read_to_string(&info) {
+                Ok(contents) => contents,
+                // Devices that were only discovered have no info file
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                // One bad file should not hide every other paired device
+                Err(e) => {
+                    log::warn!("skipping {}: {}", info.display(), e);
+                    continue;
+                }
+            };
+            let last_seen = std::fs::metadata(&info)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|t| DateTime::<Utc>::from(t).to_rfc3339());
+
+            let Some(mut record) = parse_info(&parse_key_file(&contents)) else {
+                continue;
+            };
+            record.adapter = file_name(&adapter);
+            record.address = file_name(&device);
+            record.last_seen = last_seen;
+            if cfg.hash_addresses {
+                record.address = hash_address(&record.address, cfg.hash_key.as_deref());
+            }
+            devices.push(record);
+        }
+    }
+    Ok(devices)
+}
+
+/// Subdirectories named like a Bluetooth address (`AA:BB:CC:DD:EE:FF`), sorted.
+fn list_address_dirs(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut dirs = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to list {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && is_address(&file_name(p)))
+        .collect::<Vec<_>>();
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn is_address(name: &str) -> bool {
+    let parts: Vec<_> = name.split(':').collect();
+    parts.len() == 6
+        && parts
+            .iter()
+            .all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Parse a GLib key file (`[Section]` headers and `key=value` lines).
+fn parse_key_file(contents: &str) -> KeyFile {
+    let mut file = KeyFile::new();
+    let mut section = String::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.to_string();
+            file.entry(section.clone()).or_default();
+        } else if let Some((key, value)) = line.split_once('=') {
+            file.entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    file
+}
+
+/// Build a device record from an `info` key file. Returns `None` unless the device is bonded or
+/// trusted. Address, adapter and last seen are filled in by the caller.
+fn parse_info(info: &KeyFile) -> Option<BluetoothDevice> {
+    let general = info.get("General")?;
+    let flag = |key: &str| general.get(key).map(String::as_str) == Some("true");
+    let paired = KEY_SECTIONS.iter().any(|s| info.contains_key(*s));
+    let trusted = flag("Trusted");
+    if !paired && !trusted {
+        return None;
+    }
+
+    let class = general
+        .get("Class")
+        .and_then(|c| u32::from_str_radix(c.trim_start_matches("0x"), 16).ok());
+    let appearance = general
+        .get("Appearance")
+        .and_then(|a| u16::from_str_radix(a.trim_start_matches("0x"), 16).ok());
+    let major_class = class
+        .and_then(major_device_class)
+        .or_else(|| appearance.and_then(appearance_category));
+
+    Some(BluetoothDevice {
+        adapter: String::new(),
+        address: String::new(),
+        address_type: general.get("AddressType").cloned(),
+        name: general
+            .get("Alias")
+            .or_else(|| general.get("Name"))
+            .cloned(),
+        device_class: class.map(|c| format!("0x{:06x}", c)),
+        major_class: major_class.map(str::to_string),
+        paired,
+        trusted,
+        blocked: flag("Blocked"),
+        last_seen: None,
+    })
+}
+
+/// Major device class from bits 8-12 of a BR/EDR Class of Device.
+fn major_device_class(class: u32) -> Option<&'static str> {
+    Some(match (class >> 8) & 0x1f {
+        0x01 => "Computer",
+        0x02 => "Phone",
+        0x03 => "Network Access Point",
+        0x04 => "Audio/Video",
+        0x05 => "Peripheral",
+        0x06 => "Imaging",
+        0x07 => "Wearable",
+        0x08 => "Toy",
+        0x09 => "Health",
+        _ => return None,
+    })
+}
+
+/// Category (upper 10 bits) of a Bluetooth LE GAP Appearance value.
+fn appearance_category(appearance: u16) -> Option<&'static str> {
+    Some(match appearance >> 6 {
+        0x01 => "Phone",
+        0x02 => "Computer",
+        0x03 => "Watch",
+        0x05 => "Display",
+        0x06 => "Remote Control",
+        0x08 => "Tag",
+        0x0a => "Media Player",
+        0x0d => "Heart Rate Sensor",
+        0x0f => "Human Interface Device",
+        0x25 => "Audio Sink",
+        _ => return None,
+    })
+}
+
+/// HMAC-SHA256 of the uppercase address, hex encoded. Without a key the hash is deterministic
+/// across all fleets, so only a keyed hash keeps addresses from being recovered by enumeration.
+fn hash_address(address: &str, key: Option<&str>) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.unwrap_or_default().as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(address.to_ascii_uppercase().as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADSET_INFO: &str = "\
+[General]
+Name=WH-1000XM4
+Class=0x240404
+SupportedTechnologies=BR/EDR;
+Trusted=true
+Blocked=false
+Services=0000110b-0000-1000-8000-00805f9b34fb;
+
+[LinkKey]
+Key=8D8C0A4E1C2D3F405162738495A6B7C8
+Type=4
+PINLength=0
+";
+
+    const MOUSE_INFO: &str = "\
+[General]
+Name=MX Master 3
+Alias=Work mouse
+Appearance=0x03c2
+AddressType=static
+SupportedTechnologies=LE;
+Trusted=false
+Blocked=false
+
+[LongTermKey]
+Key=00112233445566778899AABBCCDDEEFF
+Authenticated=0
+EncSize=16
+";
+
+    const DISCOVERED_INFO: &str = "\
+[General]
+Name=Living Room TV
+Class=0x08043c
+Trusted=false
+Blocked=false
+";
+
+    #[test]
+    fn test_parse_bonded_devices() {
+        let headset = parse_info(&parse_key_file(HEADSET_INFO)).unwrap();
+        assert_eq!(headset.name.as_deref(), Some("WH-1000XM4"));
+        assert_eq!(headset.device_class.as_deref(), Some("0x240404"));
+        assert_eq!(headset.major_class.as_deref(), Some("Audio/Video"));
+        assert!(headset.paired && headset.trusted && !headset.blocked);
+        assert_eq!(headset.address_type, None);
+
+        // LE devices have no Class; the alias wins over the advertised name
+        let mouse = parse_info(&parse_key_file(MOUSE_INFO)).unwrap();
+        assert_eq!(mouse.name.as_deref(), Some("Work mouse"));
+        assert_eq!(mouse.device_class, None);
+        assert_eq!(mouse.major_class.as_deref(), Some("Human Interface Device"));
+        assert_eq!(mouse.address_type.as_deref(), Some("static"));
+        assert!(mouse.paired && !mouse.trusted);
+    }
+
+    #[test]
+    fn test_unpaired_untrusted_device_is_skipped() {
+        assert!(parse_info(&parse_key_file(DISCOVERED_INFO)).is_none());
+    }
+
+    #[test]
+    fn test_hash_address() {
+        let plain = hash_address("aa:bb:cc:dd:ee:ff", Some("fleet-key"));
+        assert_eq!(plain.len(), 64);
+        assert_eq!(plain, hash_address("AA:BB:CC:DD:EE:FF", Some("fleet-key")));
+        assert_ne!(plain, hash_address("AA:BB:CC:DD:EE:FF", Some("other-key")));
+        assert_ne!(plain, hash_address("AA:BB:CC:DD:EE:FF", None));
+    }
+
+    // Address directory names contain ':', which Windows does not allow, so the tree is built
+    // at test time instead of living under tests/fixtures
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_from_storage_tree() {
+        let root = std::env::temp_dir().join("inventory-agent-bluetooth-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let adapter = root.join(BLUEZ_STORAGE).join("3C:58:C2:11:22:33");
+        for (address, info) in [
+            ("38:18:4C:AA:BB:CC", HEADSET_INFO),
+            ("F1:E2:D3:C4:B5:A6", MOUSE_INFO),
+            ("00:1A:7D:DA:71:13", DISCOVERED_INFO),
+        ] {
+            std::fs::create_dir_all(adapter.join(address)).unwrap();
+            std::fs::write(adapter.join(address).join("info"), info).unwrap();
+        }
+        // Name cache for a device seen in a scan, and adapter-level files
+        std::fs::create_dir_all(adapter.join("cache")).unwrap();
+        std::fs::write(adapter.join("settings"), "[General]\nDiscoverable=false\n").unwrap();
+
+        let devices = collect(&root, &BluetoothConfig::default()).unwrap();
+        let addresses: Vec<_> = devices.iter().map(|d| d.address.as_str()).collect();
+        assert_eq!(addresses, vec!["38:18:4C:AA:BB:CC", "F1:E2:D3:C4:B5:A6"]);
+        assert_eq!(devices[0].adapter, "3C:58:C2:11:22:33");
+        assert!(devices[0].last_seen.is_some());
+
+        let cfg = BluetoothConfig {
+            hash_addresses: true,
+            hash_key: Some("fleet-key".to_string()),
+        };
+        let hashed = collect(&root, &cfg).unwrap();
+        assert_eq!(
+            hashed[0].address,
+            hash_address("38:18:4C:AA:BB:CC", Some("fleet-key"))
+        );
+        // The adapter is the machine's own radio and stays readable
+        assert_eq!(hashed[0].adapter, "3C:58:C2:11:22:33");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_info_is_skipped() {
+        let root = std::env::temp_dir().join("inventory-agent-bluetooth-unreadable-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let adapter = root.join(BLUEZ_STORAGE).join("3C:58:C2:11:22:33");
+        std::fs::create_dir_all(adapter.join("38:18:4C:AA:BB:CC")).unwrap();
+        std::fs::write(adapter.join("38:18:4C:AA:BB:CC/info"), HEADSET_INFO).unwrap();
+        // Not UTF-8, so reading it fails with something other than NotFound
+        std::fs::create_dir_all(adapter.join("00:1A:7D:DA:71:13")).unwrap();
+        std::fs::write(
+            adapter.join("00:1A:7D:DA:71:13/info"),
+            b"[General]\nName=\xff\xfe\n",
+        )
+        .unwrap();
+
+        let devices = collect(&root, &BluetoothConfig::default()).unwrap();
+        let addresses: Vec<_> = devices.iter().map(|d| d.address.as_str()).collect();
+        assert_eq!(addresses, vec!["38:18:4C:AA:BB:CC"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_missing_storage() {
+        let devices = collect(Path::new("/nonexistent"), &BluetoothConfig::default()).unwrap();
+        assert!(devices.is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+//! CPU side-channel vulnerability status from `/sys/devices/system/cpu/vulnerabilities` and the
+//! loaded microcode revision.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::read_attr;
+use crate::models::{CpuSecurity, CpuVulnerability, VulnerabilityStatus};
+
+const VULNERABILITIES_DIR: &str = "sys/devices/system/cpu/vulnerabilities";
+const CPUINFO: &str = "proc/cpuinfo";
+
+/// Read vulnerability and microcode state. Returns `None` on kernels without the
+/// vulnerabilities directory (before 4.15) and on non-Linux hosts.
+pub fn collect(root: &Path) -> Result<Option<CpuSecurity>> {
+    let dir = root.join(VULNERABILITIES_DIR);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    let vulnerabilities: Vec<CpuVulnerability> = names
+        .into_iter()
+        .filter_map(|name| {
+            let detail = read_attr(&dir, &name)?;
+            Some(CpuVulnerability {
+                status: classify(&detail),
+                name,
+                detail,
+            })
+        })
+        .collect();
+
+    let cpuinfo = std::fs::read_to_string(root.join(CPUINFO)).unwrap_or_default();
+    // cpuinfo has no microcode line on some virtual machines and architectures
+    let microcode_revision = cpuinfo_field(&cpuinfo, "microcode")
+        .map(str::to_string)
+        .or_else(|| {
+            read_attr(
+                &root.join("sys/devices/system/cpu/cpu0/microcode"),
+                "version",
+            )
+        });
+
+    Ok(Some(CpuSecurity {
+        vendor: cpuinfo_field(&cpuinfo, "vendor_id").map(str::to_string),
+        model_name: cpuinfo_field(&cpuinfo, "model name").map(str::to_string),
+        microcode_revision,
+        any_vulnerable: vulnerabilities
+            .iter()
+            .any(|v| v.status == VulnerabilityStatus::Vulnerable),
+        vulnerabilities,
+    }))
+}
+
+/// Classify a vulnerabilities file, e.g. "Mitigation: PTI", "Not affected" or
+/// "Vulnerable: Clear CPU buffers attempted, no microcode; SMT vulnerable".
+pub fn classify(detail: &str) -> VulnerabilityStatus {
+    // itlb_multihit reports on behalf of KVM, e.g. "KVM: Mitigation: VMX disabled"
+    let detail = detail.strip_prefix("KVM: ").unwrap_or(detail);
+    if detail.starts_with("Not affected") {
+        VulnerabilityStatus::NotAffected
+    } else if detail.starts_with("Mitigation") {
+        VulnerabilityStatus::Mitigated
+    } else if detail.starts_with("Vulnerable") || detail.starts_with("Processor vulnerable") {
+        VulnerabilityStatus::Vulnerable
+    } else {
+        VulnerabilityStatus::Unknown
+    }
+}
+
+/// Value of `key` for the first processor in `/proc/cpuinfo`.
+pub(crate) fn cpuinfo_field<'a>(cpuinfo: &'a str, key: &str) -> Option<&'a str> {
+    cpuinfo
+        .lines()
+        .find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim())
+        })
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cpu")
+    }
+
+    #[test]
+    fn test_collect() {
+        let cpu = collect(&fixture_root()).unwrap().unwrap();
+        assert_eq!(cpu.vendor.as_deref(), Some("GenuineIntel"));
+        assert_eq!(
+            cpu.model_name.as_deref(),
+            Some("Intel(R) Core(TM) i7-8650U CPU @ 1.90GHz")
+        );
+        assert_eq!(cpu.microcode_revision.as_deref(), Some("0xf4"));
+        assert!(cpu.any_vulnerable);
+
+        let statuses: Vec<(&str, VulnerabilityStatus)> = cpu
+            .vulnerabilities
+            .iter()
+            .map(|v| (v.name.as_str(), v.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("gather_data_sampling", VulnerabilityStatus::Vulnerable),
+                ("itlb_multihit", VulnerabilityStatus::Mitigated),
+                ("meltdown", VulnerabilityStatus::Mitigated),
+                ("retbleed", VulnerabilityStatus::Mitigated),
+                ("spectre_v2", VulnerabilityStatus::Mitigated),
+                ("srbds", VulnerabilityStatus::Mitigated),
+                ("tsx_async_abort", VulnerabilityStatus::NotAffected),
+            ]
+        );
+        assert_eq!(cpu.vulnerabilities[0].detail, "Vulnerable: No microcode");
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("Not affected"), VulnerabilityStatus::NotAffected);
+        assert_eq!(classify("Mitigation: PTI"), VulnerabilityStatus::Mitigated);
+        assert_eq!(
+            classify("Vulnerable: Clear CPU buffers attempted, no microcode; SMT vulnerable"),
+            VulnerabilityStatus::Vulnerable
+        );
+        assert_eq!(classify("KVM: Vulnerable"), VulnerabilityStatus::Vulnerable);
+        assert_eq!(
+            classify("Unknown: Dependent on hypervisor status"),
+            VulnerabilityStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn test_microcode_from_sysfs() {
+        let root = std::env::temp_dir().join("inventory-agent-cpu-test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join(VULNERABILITIES_DIR)).unwrap();
+        std::fs::create_dir_all(root.join("sys/devices/system/cpu/cpu0/microcode")).unwrap();
+        std::fs::write(
+            root.join(VULNERABILITIES_DIR).join("meltdown"),
+            "Not affected\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("sys/devices/system/cpu/cpu0/microcode/version"),
+            "0xa0011d5\n",
+        )
+        .unwrap();
+        let cpu = collect(&root).unwrap().unwrap();
+        assert_eq!(cpu.microcode_revision.as_deref(), Some("0xa0011d5"));
+        assert_eq!(cpu.vendor, None);
+        assert!(!cpu.any_vulnerable);
+    }
+
+    #[test]
+    fn test_no_vulnerabilities_dir() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
@@ -0,0 +1,211 @@
+//! System identity from the SMBIOS tables exposed in `/sys/class/dmi/id`. On Windows the same
+//! tables are read through WMI by the collector and cleaned up with [`normalize`].
+//!
+//! OEM boards often leave fields at the firmware vendor's template values, which are reported
+//! as absent rather than as data.
+
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use super::read_attr;
+use crate::models::SystemIdentity;
+
+pub(crate) const DMI_DIR: &str = "sys/class/dmi/id";
+
+/// Template strings left in unconfigured SMBIOS fields, compared case-insensitively.
+const PLACEHOLDERS: &[&str] = &[
+    "To be filled by O.E.M.",
+    "Default string",
+    "System manufacturer",
+    "System Product Name",
+    "System Version",
+    "System SKU",
+    "SKU",
+    "Not Specified",
+    "Not Applicable",
+    "None",
+    "O.E.M.",
+    "OEM",
+    "Type1ProductConfigId",
+    "Type1Family",
+    "0123456789",
+    "Unknown",
+    "N/A",
+];
+
+/// UUIDs firmware reports when none was programmed.
+const PLACEHOLDER_UUIDS: &[&str] = &[
+    "00000000-0000-0000-0000-000000000000",
+    "ffffffff-ffff-ffff-ffff-ffffffffffff",
+    "03000200-0400-0500-0006-000700080009",
+];
+
+/// Read the system identity. Returns `None` where there are no DMI tables (non-Linux hosts,
+/// some ARM boards and Xen PV guests).
+///
+/// `product_uuid` is only readable by root; it is absent when running unprivileged.
+pub fn collect(root: &Path) -> Result<Option<SystemIdentity>> {
+    let dir = root.join(DMI_DIR);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    let field = |name: &str| read_attr(&dir, name);
+
+    Ok(Some(normalize(SystemIdentity {
+        manufacturer: field("sys_vendor"),
+        product_name: field("product_name"),
+        product_version: field("product_version"),
+        product_family: field("product_family"),
+        sku: field("product_sku"),
+        uuid: field("product_uuid"),
+        board_vendor: field("board_vendor"),
+        board_name: field("board_name"),
+        bios_vendor: field("bios_vendor"),
+        bios_version: field("bios_version"),
+        bios_release_date: field("bios_date"),
+    })))
+}
+
+/// Clean up identity fields as read from the firmware: trim them, drop template values and
+/// placeholder UUIDs, lowercase the UUID and convert the BIOS date to ISO 8601.
+pub fn normalize(raw: SystemIdentity) -> SystemIdentity {
+    let field = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty() && !is_placeholder(v))
+    };
+    SystemIdentity {
+        manufacturer: field(raw.manufacturer),
+        product_name: field(raw.product_name),
+        product_version: field(raw.product_version),
+        product_family: field(raw.product_family),
+        sku: field(raw.sku),
+        uuid: field(raw.uuid)
+            .map(|u| u.to_lowercase())
+            .filter(|u| !PLACEHOLDER_UUIDS.contains(&u.as_str())),
+        board_vendor: field(raw.board_vendor),
+        board_name: field(raw.board_name),
+        bios_vendor: field(raw.bios_vendor),
+        bios_version: field(raw.bios_version),
+        bios_release_date: field(raw.bios_release_date).map(|d| normalize_date(&d)),
+    }
+}
+
+/// Whether `value` is an SMBIOS template string rather than real data.
+pub(crate) fn is_placeholder(value: &str) -> bool {
+    PLACEHOLDERS.iter().any(|p| p.eq_ignore_ascii_case(value))
+}
+
+/// SMBIOS dates are `MM/DD/YYYY`, and WMI reports them as CIM datetimes
+/// (`20230224000000.000000+000`); reported as ISO 8601 when they parse, verbatim otherwise.
+fn normalize_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%m/%d/%Y")
+        .or_else(|e| {
+            let day = date.get(..8).filter(|_| date.len() == 25).ok_or(e)?;
+            NaiveDate::parse_from_str(day, "%Y%m%d")
+        })
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/dmi")
+            .join(name)
+    }
+
+    #[test]
+    fn test_laptop() {
+        let id = collect(&fixture("thinkpad")).unwrap().unwrap();
+        assert_eq!(id.manufacturer.as_deref(), Some("LENOVO"));
+        assert_eq!(id.product_name.as_deref(), Some("20L8S02D00"));
+        assert_eq!(id.product_version.as_deref(), Some("ThinkPad T480s"));
+        assert_eq!(id.product_family.as_deref(), Some("ThinkPad T480s"));
+        assert_eq!(
+            id.sku.as_deref(),
+            Some("LENOVO_MT_20L8_BU_Think_FM_ThinkPad T480s")
+        );
+        assert_eq!(
+            id.uuid.as_deref(),
+            Some("4c4c4544-0042-3510-8052-b4c04f4e3132")
+        );
+        assert_eq!(id.bios_vendor.as_deref(), Some("LENOVO"));
+        assert_eq!(id.bios_version.as_deref(), Some("N22ET80W (1.57 )"));
+        assert_eq!(id.bios_release_date.as_deref(), Some("2023-02-24"));
+    }
+
+    #[test]
+    fn test_placeholders_absent() {
+        let id = collect(&fixture("whitebox")).unwrap().unwrap();
+        assert_eq!(id.manufacturer, None);
+        assert_eq!(id.product_name, None);
+        assert_eq!(id.product_version, None);
+        assert_eq!(id.sku, None);
+        assert_eq!(id.uuid, None);
+        assert_eq!(id.board_vendor.as_deref(), Some("ASUSTeK COMPUTER INC."));
+        assert_eq!(id.board_name.as_deref(), Some("PRIME B450M-A"));
+        assert_eq!(id.bios_vendor.as_deref(), Some("American Megatrends Inc."));
+        // Not a SMBIOS date; kept as is
+        assert_eq!(id.bios_release_date.as_deref(), Some("2019"));
+    }
+
+    #[test]
+    fn test_is_placeholder() {
+        assert!(is_placeholder("To be filled by O.E.M."));
+        assert!(is_placeholder("default string"));
+        assert!(!is_placeholder("Dell Inc."));
+    }
+
+    #[test]
+    fn test_normalize_wmi_fields() {
+        let raw = SystemIdentity {
+            manufacturer: Some("Dell Inc.".to_string()),
+            product_name: Some("Latitude 7440".to_string()),
+            product_version: None,
+            product_family: Some("Latitude".to_string()),
+            sku: Some("0C08".to_string()),
+            uuid: Some("4C4C4544-0042-3510-8052-B4C04F4E3132".to_string()),
+            board_vendor: Some("Dell Inc.".to_string()),
+            board_name: Some("0T4KFY  ".to_string()),
+            bios_vendor: Some("Dell Inc.".to_string()),
+            bios_version: Some("1.12.1".to_string()),
+            bios_release_date: Some("20240117000000.000000+000".to_string()),
+        };
+        let id = normalize(raw);
+        assert_eq!(
+            id.uuid.as_deref(),
+            Some("4c4c4544-0042-3510-8052-b4c04f4e3132")
+        );
+        assert_eq!(id.board_name.as_deref(), Some("0T4KFY"));
+        assert_eq!(id.bios_release_date.as_deref(), Some("2024-01-17"));
+
+        let blank = normalize(SystemIdentity {
+            manufacturer: Some("System manufacturer".to_string()),
+            uuid: Some("FFFFFFFF-FFFF-FFFF-FFFF-FFFFFFFFFFFF".to_string()),
+            sku: Some("  ".to_string()),
+            ..id
+        });
+        assert_eq!(blank.manufacturer, None);
+        assert_eq!(blank.uuid, None);
+        assert_eq!(blank.sku, None);
+    }
+
+    #[test]
+    fn test_normalize_date() {
+        assert_eq!(normalize_date("02/24/2023"), "2023-02-24");
+        assert_eq!(normalize_date("20230224000000.000000+000"), "2023-02-24");
+        assert_eq!(normalize_date("2019"), "2019");
+    }
+
+    #[test]
+    fn test_no_dmi() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
@@ -0,0 +1,352 @@
+//! Per-volume disk encryption status on Linux.
+//!
+//! Mounted filesystems come from `/proc/mounts`. A volume is encrypted when its block device,
+//! or any device below it (LVM on LUKS), is a dm-crypt mapping. dm-crypt mappings carry a
+//! `CRYPT-<type>-...` UUID in sysfs, and LUKS containers describe their cipher in a header at
+//! the start of the backing partition.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use super::read_attr;
+use crate::models::{DiskEncryption, VolumeEncryption};
+
+const MOUNTS: &str = "proc/mounts";
+const SYS_BLOCK: &str = "sys/class/block";
+
+const LUKS_MAGIC: &[u8; 6] = b"LUKS\xba\xbe";
+const LUKS2_BINARY_HEADER_LEN: usize = 4096;
+/// Upper bound for the LUKS2 JSON area; cryptsetup never writes more than 4 MiB.
+const LUKS2_MAX_HEADER_LEN: u64 = 4 * 1024 * 1024;
+
+/// Details from a LUKS header.
+#[derive(Debug, PartialEq)]
+pub struct LuksHeader {
+    pub version: u16,
+    pub uuid: String,
+    /// Cipher spec as cryptsetup prints it (e.g. `aes-xts-plain64`).
+    pub cipher: Option<String>,
+}
+
+/// The dm-crypt mapping found under a volume.
+struct CryptDevice {
+    scheme: String,
+    /// Partition or disk holding the encrypted data (and the LUKS header).
+    backing: Option<String>,
+}
+
+/// Determine the encryption state of every mounted block device filesystem.
+pub fn collect(root: &Path) -> Result<Option<DiskEncryption>> {
+    let mounts_path = root.join(MOUNTS);
+    let mounts = match std::fs::read_to_string(&mounts_path) {
+        Ok(mounts) => mounts,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read {}", mounts_path.display()))
+        }
+    };
+
+    let mut volumes: Vec<VolumeEncryption> = Vec::new();
+    for line in mounts.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [device, mount_point, filesystem, ..] = fields[..] else {
+            continue;
+        };
+        if !device.starts_with("/dev/") || volumes.iter().any(|v| v.device == device) {
+            continue;
+        }
+        let mount_point = unescape_mount_field(mount_point);
+        let block = block_name(root, device);
+        let crypt = block.as_deref().and_then(|b| find_crypt(root, b, 0));
+
+        let header = crypt
+            .as_ref()
+            .and_then(|c| c.backing.as_deref())
+            .and_then(|b| match read_luks_header(&root.join("dev").join(b)) {
+                Ok(header) => Some(header),
+                Err(e) => {
+                    log::debug!("no LUKS header read from {}: {:#}", b, e);
+                    None
+                }
+            });
+
+        volumes.push(VolumeEncryption {
+            device: device.to_string(),
+            system_volume: mount_point == "/",
+            mount_point,
+            filesystem: filesystem.to_string(),
+            encrypted: crypt.is_some(),
+            scheme: crypt.as_ref().map(|c| c.scheme.clone()),
+            cipher: header.as_ref().and_then(|h| h.cipher.clone()),
+            luks_uuid: header.map(|h| h.uuid),
+            container_device: crypt.and_then(|c| c.backing),
+        });
+    }
+
+    let system_volume_encrypted = volumes
+        .iter()
+        .find(|v| v.system_volume)
+        .map(|v| v.encrypted);
+    Ok(Some(DiskEncryption {
+        volumes,
+        system_volume_encrypted,
+    }))
+}
+
+/// Resolve a `/dev` path to its kernel block device name (`dm-0`, `nvme0n1p2`).
+fn block_name(root: &Path, device: &str) -> Option<String> {
+    let name = device.strip_prefix("/dev/")?;
+    let Some(mapper_name) = name.strip_prefix("mapper/") else {
+        return Some(name.to_string());
+    };
+    // /dev/mapper names are symlinks to dm-N; find the mapping by its name in sysfs
+    std::fs::read_dir(root.join(SYS_BLOCK))
+        .ok()?
+        .filter_map(|e| e.ok())
+        .find(|e| read_attr(&e.path().join("dm"), "name").as_deref() == Some(mapper_name))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+}
+
+/// Walk from `block` down through its slave devices looking for a dm-crypt mapping.
+fn find_crypt(root: &Path, block: &str, depth: usize) -> Option<CryptDevice> {
+    // Device stacks are shallow; the limit only guards against sysfs loops
+    if depth > 8 {
+        return None;
+    }
+    let dir = root.join(SYS_BLOCK).join(block);
+    let slaves = list_slaves(&dir);
+
+    if let Some(uuid) = read_attr(&dir.join("dm"), "uuid") {
+        if let Some(rest) = uuid.strip_prefix("CRYPT-") {
+            let kind = rest.split('-').next().unwrap_or_default();
+            return Some(CryptDevice {
+                scheme: scheme_name(kind),
+                backing: slaves.into_iter().next(),
+            });
+        }
+    }
+    slaves
+        .iter()
+        .find_map(|slave| find_crypt(root, slave, depth + 1))
+}
+
+fn list_slaves(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir.join("slaves")) else {
+        return Vec::new();
+    };
+    let mut slaves: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    slaves.sort();
+    slaves
+}
+
+/// Readable name for the type in a `CRYPT-<type>-` device-mapper UUID.
+fn scheme_name(kind: &str) -> String {
+    match kind {
+        "LUKS1" | "LUKS2" => kind.to_string(),
+        "PLAIN" => "plain dm-crypt".to_string(),
+        "BITLK" => "BitLocker".to_string(),
+        "TCRYPT" => "TrueCrypt/VeraCrypt".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// `/proc/mounts` escapes spaces and other whitespace as octal (`\040`).
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if let (b'\\', Some([a, b, c])) = (bytes[i], bytes.get(i + 1..i + 4)) {
+            let octal = std::str::from_utf8(&[*a, *b, *c])
+                .ok()
+                .and_then(|o| u8::from_str_radix(o, 8).ok());
+            if let Some(byte) = octal {
+                out.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Read and parse the LUKS header at the start of `path`.
+pub fn read_luks_header(path: &Path) -> Result<LuksHeader> {
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut data = Vec::new();
+    (&mut file)
+        .take(LUKS2_BINARY_HEADER_LEN as u64)
+        .read_to_end(&mut data)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    // LUKS2 keeps its metadata in a JSON area after the binary header
+    if data.len() >= 16 && &data[..6] == LUKS_MAGIC && data[6..8] == [0, 2] {
+        let hdr_size = u64::from_be_bytes(data[8..16].try_into().unwrap());
+        if hdr_size > LUKS2_MAX_HEADER_LEN {
+            bail!("LUKS2 header size {} is too large", hdr_size);
+        }
+        file.take(hdr_size.saturating_sub(data.len() as u64))
+            .read_to_end(&mut data)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+    }
+    parse_luks_header(&data)
+}
+
+/// Parse a LUKS1 or LUKS2 header.
+pub fn parse_luks_header(data: &[u8]) -> Result<LuksHeader> {
+    if data.len() < 208 || &data[..6] != LUKS_MAGIC {
+        bail!("no LUKS header");
+    }
+    let version = u16::from_be_bytes([data[6], data[7]]);
+    let uuid = c_string(&data[168..208]);
+
+    let cipher = match version {
+        1 => {
+            let name = c_string(&data[8..40]);
+            let mode = c_string(&data[40..72]);
+            Some(format!("{}-{}", name, mode))
+        }
+        2 => {
+            let json = data
+                .get(LUKS2_BINARY_HEADER_LEN..)
+                .context("LUKS2 header is missing its JSON area")?;
+            let end = json.iter().position(|b| *b == 0).unwrap_or(json.len());
+            let metadata: Luks2Metadata = serde_json::from_slice(&json[..end])
+                .context("failed to parse LUKS2 JSON metadata")?;
+            luks2_cipher(&metadata)
+        }
+        v => bail!("unsupported LUKS version {}", v),
+    };
+
+    Ok(LuksHeader {
+        version,
+        uuid,
+        cipher,
+    })
+}
+
+#[derive(Deserialize)]
+struct Luks2Metadata {
+    #[serde(default)]
+    segments: std::collections::BTreeMap<String, Luks2Segment>,
+}
+
+#[derive(Deserialize)]
+struct Luks2Segment {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    encryption: Option<String>,
+}
+
+/// Cipher of the first crypt segment (segments are keyed by their index as a string).
+fn luks2_cipher(metadata: &Luks2Metadata) -> Option<String> {
+    let mut segments: Vec<(u32, &Luks2Segment)> = metadata
+        .segments
+        .iter()
+        .filter_map(|(k, s)| Some((k.parse().ok()?, s)))
+        .collect();
+    segments.sort_by_key(|(k, _)| *k);
+    segments
+        .into_iter()
+        .find(|(_, s)| s.kind == "crypt")
+        .and_then(|(_, s)| s.encryption.clone())
+}
+
+fn c_string(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/encryption")
+            .join(name)
+    }
+
+    #[test]
+    fn test_parse_luks2_header() {
+        let header = read_luks_header(&fixture_root("laptop").join("dev/nvme0n1p3")).unwrap();
+        assert_eq!(header.version, 2);
+        assert_eq!(header.uuid, "3f6b1c2d-9e8a-4b7c-8d9e-0f1a2b3c4d5e");
+        assert_eq!(header.cipher.as_deref(), Some("aes-xts-plain64"));
+    }
+
+    #[test]
+    fn test_parse_luks1_header() {
+        let header = read_luks_header(&fixture_root("desktop").join("dev/sdb1")).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.uuid, "8a1d0c52-7f7e-4d6b-a0c3-51b8e2f9d411");
+        assert_eq!(header.cipher.as_deref(), Some("aes-cbc-essiv:sha256"));
+    }
+
+    #[test]
+    fn test_rejects_non_luks_data() {
+        assert!(parse_luks_header(&[0u8; 4096]).is_err());
+        assert!(parse_luks_header(b"LUKS\xba\xbe").is_err(), "truncated");
+    }
+
+    #[test]
+    fn test_lvm_on_luks_root() {
+        let status = collect(&fixture_root("laptop")).unwrap().unwrap();
+        let mounts: Vec<_> = status
+            .volumes
+            .iter()
+            .map(|v| v.mount_point.as_str())
+            .collect();
+        assert_eq!(mounts, vec!["/", "/boot", "/boot/efi"]);
+        assert_eq!(status.system_volume_encrypted, Some(true));
+
+        let root = &status.volumes[0];
+        assert_eq!(root.device, "/dev/mapper/vg-root");
+        assert!(root.encrypted && root.system_volume);
+        assert_eq!(root.scheme.as_deref(), Some("LUKS2"));
+        assert_eq!(root.container_device.as_deref(), Some("nvme0n1p3"));
+        assert_eq!(root.cipher.as_deref(), Some("aes-xts-plain64"));
+
+        let boot = &status.volumes[1];
+        assert!(!boot.encrypted);
+        assert_eq!(boot.scheme, None);
+    }
+
+    #[test]
+    fn test_unencrypted_root_with_encrypted_data_volume() {
+        let status = collect(&fixture_root("desktop")).unwrap().unwrap();
+        assert_eq!(status.system_volume_encrypted, Some(false));
+
+        let data = status
+            .volumes
+            .iter()
+            .find(|v| v.mount_point == "/srv/team data")
+            .unwrap();
+        assert!(data.encrypted);
+        assert_eq!(data.scheme.as_deref(), Some("LUKS1"));
+        assert_eq!(data.filesystem, "xfs");
+    }
+
+    #[test]
+    fn test_unescape_mount_field() {
+        assert_eq!(unescape_mount_field("/srv/team\\040data"), "/srv/team data");
+        // Not a valid octal byte; kept as is
+        assert_eq!(unescape_mount_field("/mnt/a\\777b"), "/mnt/a\\777b");
+    }
+
+    #[test]
+    fn test_missing_mounts() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
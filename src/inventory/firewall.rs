@@ -0,0 +1,326 @@
+//! Host firewall state from the nftables ruleset, falling back to `iptables-save` and
+//! `ip6tables-save` where `nft` is missing, fails or sees no base chains (iptables-legacy hosts).
+//!
+//! All three commands need root; the agent service has it.
+
+use std::io::ErrorKind;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::config::FirewallConfig;
+use crate::models::{FirewallBackend, FirewallChain, FirewallStatus};
+
+/// Absolute paths, so a PATH set up by the service manager cannot substitute the tools.
+const NFT: &str = "/usr/sbin/nft";
+const IPTABLES_SAVE: &str = "/usr/sbin/iptables-save";
+const IP6TABLES_SAVE: &str = "/usr/sbin/ip6tables-save";
+
+/// Policies that drop traffic not matched by a rule.
+const RESTRICTIVE_POLICIES: &[&str] = &["drop", "reject"];
+
+/// Read the ruleset and summarize it. Returns `None` when no tool is installed.
+pub fn collect(cfg: &FirewallConfig) -> Result<Option<FirewallStatus>> {
+    let marker = cfg.managed_marker.as_deref();
+    let nft = run(NFT, &["-j", "list", "ruleset"])
+        .and_then(|json| json.map(|j| parse_nft_json(&j, marker)).transpose());
+    if matches!(&nft, Ok(Some(status)) if !status.chains.is_empty()) {
+        return nft;
+    }
+
+    // Rules loaded through iptables-legacy are invisible to nft
+    let legacy: Result<Vec<(&str, String)>> = [("ip", IPTABLES_SAVE), ("ip6", IP6TABLES_SAVE)]
+        .into_iter()
+        .filter_map(|(family, program)| {
+            run(program, &[])
+                .transpose()
+                .map(|text| text.map(|t| (family, t)))
+        })
+        .collect();
+    match (nft, legacy) {
+        (_, Ok(dumps)) if !dumps.is_empty() => Ok(Some(parse_iptables_save(&dumps, marker))),
+        (Ok(Some(status)), _) => Ok(Some(status)),
+        (Err(e), _) => Err(e),
+        (Ok(None), legacy) => legacy.map(|_| None),
+    }
+}
+
+/// Run a command and return its stdout, or `None` if it is not installed.
+fn run(program: &str, args: &[&str]) -> Result<Option<String>> {
+    let output = match Command::new(program).args(args).output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("failed to run {}", program)),
+    };
+    if !output.status.success() {
+        bail!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+/// Parse `nft -j list ruleset` output.
+pub fn parse_nft_json(json: &str, marker: Option<&str>) -> Result<FirewallStatus> {
+    let root: Value = serde_json::from_str(json).context("invalid nft JSON")?;
+    let items = root
+        .get("nftables")
+        .and_then(Value::as_array)
+        .context("nft JSON has no nftables array")?;
+
+    let mut tables = Vec::new();
+    let mut chains: Vec<FirewallChain> = Vec::new();
+    let mut rules = Vec::new();
+    let mut comments = Vec::new();
+    for item in items {
+        if let Some(table) = item.get("table") {
+            tables.push(str_field(table, "name").unwrap_or_default());
+        } else if let Some(chain) = item.get("chain") {
+            // Regular chains are only reachable by jumps; report the base chains
+            let Some(hook) = str_field(chain, "hook") else {
+                continue;
+            };
+            chains.push(FirewallChain {
+                family: str_field(chain, "family").unwrap_or_default(),
+                table: str_field(chain, "table").unwrap_or_default(),
+                name: str_field(chain, "name").unwrap_or_default(),
+                hook,
+                policy: str_field(chain, "policy"),
+                rule_count: 0,
+            });
+        } else if let Some(rule) = item.get("rule") {
+            rules.push((
+                str_field(rule, "family").unwrap_or_default(),
+                str_field(rule, "table").unwrap_or_default(),
+                str_field(rule, "chain").unwrap_or_default(),
+            ));
+            comments.extend(str_field(rule, "comment"));
+        }
+    }
+
+    for chain in &mut chains {
+        chain.rule_count = rules
+            .iter()
+            .filter(|(f, t, c)| *f == chain.family && *t == chain.table && *c == chain.name)
+            .count();
+    }
+    let manager = if tables.iter().any(|t| t == "firewalld") {
+        Some("firewalld")
+    } else if rules.iter().any(|(_, _, c)| c.starts_with("ufw-")) {
+        Some("ufw")
+    } else {
+        None
+    };
+    let managed_marker_present = marker.is_some_and(|m| {
+        tables.iter().any(|t| t.contains(m)) || comments.iter().any(|c| c.contains(m))
+    });
+
+    Ok(summarize(
+        FirewallBackend::Nftables,
+        chains,
+        rules.len(),
+        manager,
+        managed_marker_present,
+    ))
+}
+
+/// Parse `iptables-save` and `ip6tables-save` output, given with its family (`ip` or `ip6`).
+/// `*table` starts a table, `:CHAIN POLICY [counters]` declares a chain and `-A CHAIN ...`
+/// appends a rule.
+pub fn parse_iptables_save(dumps: &[(&str, String)], marker: Option<&str>) -> FirewallStatus {
+    let mut table = String::new();
+    let mut chains: Vec<FirewallChain> = Vec::new();
+    let mut rule_count = 0;
+    let mut ufw = false;
+    let mut managed_marker_present = false;
+
+    let lines = dumps
+        .iter()
+        .flat_map(|(family, text)| text.lines().map(move |line| (*family, line)));
+    for (family, line) in lines {
+        if let Some(name) = line.strip_prefix('*') {
+            table = name.trim().to_string();
+        } else if let Some(decl) = line.strip_prefix(':') {
+            let mut parts = decl.split_whitespace();
+            let (Some(name), Some(policy)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            ufw |= name.starts_with("ufw-");
+            // User-defined chains have no policy ("-")
+            if policy == "-" {
+                continue;
+            }
+            chains.push(FirewallChain {
+                family: family.to_string(),
+                table: table.clone(),
+                name: name.to_string(),
+                hook: name.to_lowercase(),
+                policy: Some(policy.to_lowercase()),
+                rule_count: 0,
+            });
+        } else if let Some(rule) = line.strip_prefix("-A ") {
+            rule_count += 1;
+            let chain_name = rule.split_whitespace().next().unwrap_or_default();
+            if let Some(chain) = chains
+                .iter_mut()
+                .find(|c| c.family == family && c.table == table && c.name == chain_name)
+            {
+                chain.rule_count += 1;
+            }
+            managed_marker_present |= marker.is_some_and(|m| rule.contains(m));
+        }
+    }
+
+    summarize(
+        FirewallBackend::Iptables,
+        chains,
+        rule_count,
+        ufw.then_some("ufw"),
+        managed_marker_present,
+    )
+}
+
+fn summarize(
+    backend: FirewallBackend,
+    chains: Vec<FirewallChain>,
+    rule_count: usize,
+    manager: Option<&str>,
+    managed_marker_present: bool,
+) -> FirewallStatus {
+    // Traffic must pass every input base chain, so one dropping chain makes the policy drop
+    let input: Vec<&FirewallChain> = chains.iter().filter(|c| c.hook == "input").collect();
+    let default_input_policy = if input.iter().any(|c| is_restrictive(c)) {
+        Some("drop".to_string())
+    } else if input.is_empty() {
+        None
+    } else {
+        Some("accept".to_string())
+    };
+
+    FirewallStatus {
+        backend,
+        active: rule_count > 0 || chains.iter().any(is_restrictive),
+        default_input_policy,
+        rule_count,
+        manager: manager.map(str::to_string),
+        managed_marker_present,
+        chains,
+    }
+}
+
+fn is_restrictive(chain: &FirewallChain) -> bool {
+    chain
+        .policy
+        .as_deref()
+        .is_some_and(|p| RESTRICTIVE_POLICIES.contains(&p))
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/firewall")
+            .join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_parse_nft_ruleset() {
+        let status =
+            parse_nft_json(&fixture("nft_managed.json"), Some("managed-by-ansible")).unwrap();
+        assert_eq!(status.backend, FirewallBackend::Nftables);
+        assert!(status.active);
+        assert_eq!(status.default_input_policy.as_deref(), Some("drop"));
+        assert_eq!(status.rule_count, 4);
+        assert_eq!(status.manager, None);
+        assert!(status.managed_marker_present);
+
+        let chains: Vec<_> = status
+            .chains
+            .iter()
+            .map(|c| (c.name.as_str(), c.policy.as_deref(), c.rule_count))
+            .collect();
+        // allow_ssh is a regular chain and is not listed
+        assert_eq!(
+            chains,
+            vec![
+                ("input", Some("drop"), 3),
+                ("forward", Some("drop"), 0),
+                ("output", Some("accept"), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_nft_firewalld() {
+        let status = parse_nft_json(&fixture("nft_firewalld.json"), None).unwrap();
+        assert_eq!(status.manager.as_deref(), Some("firewalld"));
+        assert!(!status.managed_marker_present);
+        // The filter_INPUT chain accepts by default but ends in a reject rule
+        assert_eq!(status.default_input_policy.as_deref(), Some("accept"));
+        assert!(status.active);
+    }
+
+    #[test]
+    fn test_parse_nft_empty_ruleset() {
+        let json = r#"{"nftables": [{"metainfo": {"version": "1.0.6", "release_name": "Lester Gooch #5", "json_schema_version": 1}}]}"#;
+        let status = parse_nft_json(json, Some("managed-by-ansible")).unwrap();
+        assert!(!status.active);
+        assert_eq!(status.default_input_policy, None);
+        assert!(status.chains.is_empty());
+        assert!(!status.managed_marker_present);
+
+        assert!(parse_nft_json("{}", None).is_err());
+    }
+
+    #[test]
+    fn test_parse_iptables_save_ufw() {
+        let dumps = [("ip", fixture("iptables_ufw.txt"))];
+        let status = parse_iptables_save(&dumps, None);
+        assert_eq!(status.backend, FirewallBackend::Iptables);
+        assert!(status.active);
+        assert_eq!(status.manager.as_deref(), Some("ufw"));
+        assert_eq!(status.default_input_policy.as_deref(), Some("drop"));
+        assert_eq!(status.rule_count, 7);
+
+        let input = status.chains.iter().find(|c| c.name == "INPUT").unwrap();
+        assert_eq!(input.hook, "input");
+        assert_eq!(input.rule_count, 2);
+    }
+
+    #[test]
+    fn test_parse_iptables_save_both_families() {
+        let dumps = [
+            ("ip", fixture("iptables_ufw.txt")),
+            ("ip6", fixture("ip6tables_legacy.txt")),
+        ];
+        let status = parse_iptables_save(&dumps, Some("managed-by-ansible"));
+        assert_eq!(status.rule_count, 9);
+        assert!(status.managed_marker_present);
+
+        let input6 = status
+            .chains
+            .iter()
+            .find(|c| c.family == "ip6" && c.name == "INPUT")
+            .unwrap();
+        assert_eq!(input6.policy.as_deref(), Some("drop"));
+        assert_eq!(input6.rule_count, 2);
+        // Same chain names in the IPv4 dump keep their own counts
+        let input4 = status
+            .chains
+            .iter()
+            .find(|c| c.family == "ip" && c.name == "INPUT")
+            .unwrap();
+        assert_eq!(input4.rule_count, 2);
+    }
+}
@@ -0,0 +1,371 @@
+//! Firmware security posture: boot mode, Secure Boot state from EFI variables and TPM presence
+//! from `/sys/class/tpm`.
+
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use super::read_attr;
+use crate::models::{BootMode, FirmwareSecurity, Tpm};
+
+const EFI_DIR: &str = "sys/firmware/efi";
+const EFIVARS: &str = "sys/firmware/efi/efivars";
+const TPM_CLASS: &str = "sys/class/tpm";
+const TPMRM_CLASS: &str = "sys/class/tpmrm";
+
+/// Character devices a TPM 2.0 accepts commands on; the resource manager first, as the raw
+/// device is exclusive.
+const TPM2_DEVICES: &[&str] = &["dev/tpmrm0", "dev/tpm0"];
+
+/// TPM2_GetCapability for the `TPM_PT_MANUFACTURER` property, without sessions.
+const TPM2_GET_MANUFACTURER: [u8; 22] = [
+    0x80, 0x01, // TPM_ST_NO_SESSIONS
+    0x00, 0x00, 0x00, 0x16, // command size
+    0x00, 0x00, 0x01, 0x7a, // TPM_CC_GetCapability
+    0x00, 0x00, 0x00, 0x06, // TPM_CAP_TPM_PROPERTIES
+    0x00, 0x00, 0x01, 0x05, // TPM_PT_MANUFACTURER
+    0x00, 0x00, 0x00, 0x01, // property count
+];
+
+/// EFI global variable vendor GUID.
+const EFI_GLOBAL_GUID: &str = "8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// Devicetree vendor prefixes of TPM chips on boards without ACPI.
+const DT_TPM_VENDORS: &[(&str, &str)] = &[
+    ("atmel", "Atmel"),
+    ("infineon", "Infineon"),
+    ("nuvoton", "Nuvoton"),
+    ("st", "STMicroelectronics"),
+];
+
+/// Vendor prefixes of TPM ACPI hardware IDs and TCG vendor IDs.
+const TPM_VENDORS: &[(&str, &str)] = &[
+    ("AMD", "AMD"),
+    ("ATML", "Atmel"),
+    ("BRCM", "Broadcom"),
+    ("IBM", "IBM"),
+    ("IFX", "Infineon"),
+    ("INTC", "Intel"),
+    ("NSM", "National Semiconductor"),
+    ("NTC", "Nuvoton"),
+    ("NTZ", "Nationz"),
+    ("QCOM", "Qualcomm"),
+    ("SMSC", "SMSC"),
+    ("STM", "STMicroelectronics"),
+    ("WEC", "Winbond"),
+];
+
+/// Read firmware security state. Returns `None` when there is no sysfs (non-Linux hosts).
+pub fn collect(root: &Path) -> Result<Option<FirmwareSecurity>> {
+    if !root.join("sys").is_dir() {
+        return Ok(None);
+    }
+
+    let uefi = root.join(EFI_DIR).is_dir();
+    let (secure_boot, setup_mode) = if uefi {
+        (
+            read_efi_bool(root, "SecureBoot"),
+            read_efi_bool(root, "SetupMode"),
+        )
+    } else {
+        (None, None)
+    };
+
+    Ok(Some(FirmwareSecurity {
+        boot_mode: if uefi {
+            BootMode::Uefi
+        } else {
+            BootMode::Legacy
+        },
+        secure_boot,
+        setup_mode,
+        tpm: read_tpm(root),
+    }))
+}
+
+/// Read a boolean EFI global variable. `None` if efivarfs is not mounted or the variable is
+/// absent or malformed.
+fn read_efi_bool(root: &Path, name: &str) -> Option<bool> {
+    let path = root
+        .join(EFIVARS)
+        .join(format!("{}-{}", name, EFI_GLOBAL_GUID));
+    let data = std::fs::read(&path).ok()?;
+    match parse_efivar_bool(&data) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("ignoring EFI variable {}: {:#}", name, e);
+            None
+        }
+    }
+}
+
+/// Parse efivarfs contents: a little-endian u32 of attributes followed by the variable data,
+/// here a single byte.
+pub fn parse_efivar_bool(data: &[u8]) -> Result<bool> {
+    match data {
+        [_, _, _, _, 0] => Ok(false),
+        [_, _, _, _, 1] => Ok(true),
+        [_, _, _, _, v] => bail!("unexpected value {}", v),
+        _ => bail!("expected 5 bytes, got {}", data.len()),
+    }
+}
+
+fn read_tpm(root: &Path) -> Option<Tpm> {
+    let dir = root.join(TPM_CLASS).join("tpm0");
+    if !dir.is_dir() {
+        return None;
+    }
+    let device = dir.join("device");
+    // TPM 1.2 chips describe themselves in `caps`; TPM 2.0 has no such attribute
+    let caps = read_attr(&device, "caps").or_else(|| read_attr(&dir, "caps"));
+
+    let version = read_attr(&dir, "tpm_version_major")
+        .map(|major| match major.as_str() {
+            "1" => "1.2".to_string(),
+            "2" => "2.0".to_string(),
+            other => other.to_string(),
+        })
+        .or_else(|| caps.as_deref().and_then(|c| caps_field(c, "TCG version")))
+        // Older kernels only expose the resource manager node for TPM 2.0
+        .or_else(|| {
+            root.join(TPMRM_CLASS)
+                .join("tpmrm0")
+                .is_dir()
+                .then(|| "2.0".to_string())
+        });
+
+    let hid = read_attr(&device.join("firmware_node"), "hid").or_else(|| read_attr(&device, "hid"));
+    let manufacturer = caps
+        .as_deref()
+        .and_then(|c| caps_field(c, "Manufacturer"))
+        .and_then(|id| vendor_from_id(&id))
+        .or_else(|| hid.as_deref().and_then(vendor_from_hid))
+        .or_else(|| {
+            let compatible = std::fs::read(device.join("of_node/compatible")).ok()?;
+            vendor_from_compatible(&compatible)
+        })
+        // Most TPM 2.0 chips use the generic MSFT0101 ID, so ask the chip itself
+        .or_else(|| {
+            (version.as_deref() == Some("2.0"))
+                .then(|| query_tpm2_manufacturer(root))
+                .flatten()
+        });
+
+    Some(Tpm {
+        version,
+        manufacturer: manufacturer.map(str::to_string),
+        description: read_attr(&device, "description"),
+    })
+}
+
+/// Value of a `Key: value` line in the TPM 1.2 `caps` attribute.
+fn caps_field(caps: &str, key: &str) -> Option<String> {
+    caps.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    })
+}
+
+/// Send TPM2_GetCapability to the TPM 2.0 device. Needs root or the `tss` group; only
+/// character devices are opened, so nothing is ever written to a regular file.
+fn query_tpm2_manufacturer(root: &Path) -> Option<&'static str> {
+    let path = TPM2_DEVICES
+        .iter()
+        .map(|d| root.join(d))
+        .find(|p| is_char_device(p))?;
+    let mut tpm = OpenOptions::new().read(true).write(true).open(path).ok()?;
+    get_tpm2_manufacturer(&mut tpm)
+}
+
+fn get_tpm2_manufacturer(tpm: &mut (impl Read + Write)) -> Option<&'static str> {
+    tpm.write_all(&TPM2_GET_MANUFACTURER).ok()?;
+    let mut response = [0u8; 64];
+    let len = tpm.read(&mut response).ok()?;
+    parse_tpm2_manufacturer(&response[..len])
+}
+
+/// Parse the GetCapability response: tag, size, response code, moreData, capability, property
+/// count, then the property and its value.
+fn parse_tpm2_manufacturer(response: &[u8]) -> Option<&'static str> {
+    let word = |at: usize| {
+        let bytes = response.get(at..at + 4)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?))
+    };
+    let success = word(6)? == 0 && word(11)? == 0x06 && word(15)? >= 1;
+    (success && word(19)? == 0x105)
+        .then(|| vendor_from_code(word(23)?))
+        .flatten()
+}
+
+#[cfg(unix)]
+fn is_char_device(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_char_device())
+}
+
+#[cfg(not(unix))]
+fn is_char_device(_path: &Path) -> bool {
+    false
+}
+
+/// Vendor from a devicetree `compatible` list such as `infineon,slb9670\0tcg,tpm_tis-spi`.
+fn vendor_from_compatible(compatible: &[u8]) -> Option<&'static str> {
+    compatible
+        .split(|b| *b == 0)
+        .filter_map(|entry| std::str::from_utf8(entry).ok()?.split_once(','))
+        .find_map(|(prefix, _)| {
+            DT_TPM_VENDORS
+                .iter()
+                .find(|(p, _)| *p == prefix)
+                .map(|(_, name)| *name)
+        })
+}
+
+/// Decode a TCG vendor ID such as `0x49465800`, which packs up to four ASCII characters.
+fn vendor_from_id(id: &str) -> Option<&'static str> {
+    vendor_from_code(u32::from_str_radix(id.trim_start_matches("0x"), 16).ok()?)
+}
+
+fn vendor_from_code(value: u32) -> Option<&'static str> {
+    let ascii: String = value
+        .to_be_bytes()
+        .iter()
+        .take_while(|b| **b != 0)
+        .map(|b| *b as char)
+        .collect();
+    vendor_name(ascii.trim())
+}
+
+/// Vendor from an ACPI hardware ID such as `IFX0102`. The generic `MSFT0101` identifies no
+/// vendor.
+fn vendor_from_hid(hid: &str) -> Option<&'static str> {
+    let prefix = hid.trim_end_matches(|c: char| c.is_ascii_digit());
+    vendor_name(prefix)
+}
+
+fn vendor_name(prefix: &str) -> Option<&'static str> {
+    TPM_VENDORS
+        .iter()
+        .find(|(p, _)| *p == prefix)
+        .map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/firmware")
+            .join(name)
+    }
+
+    #[test]
+    fn test_parse_efivar_bytes() {
+        // Attributes 0x06 (boot service and runtime access) followed by the value
+        assert!(parse_efivar_bool(&[0x06, 0, 0, 0, 0x01]).unwrap());
+        assert!(!parse_efivar_bool(&[0x06, 0, 0, 0, 0x00]).unwrap());
+        assert!(parse_efivar_bool(&[0x06, 0, 0, 0]).is_err());
+        assert!(parse_efivar_bool(&[0x06, 0, 0, 0, 0x02]).is_err());
+    }
+
+    #[test]
+    fn test_uefi_secure_boot_with_tpm2() {
+        let fw = collect(&fixture_root("uefi")).unwrap().unwrap();
+        assert_eq!(fw.boot_mode, BootMode::Uefi);
+        assert_eq!(fw.secure_boot, Some(true));
+        assert_eq!(fw.setup_mode, Some(false));
+
+        let tpm = fw.tpm.unwrap();
+        assert_eq!(tpm.version.as_deref(), Some("2.0"));
+        assert_eq!(tpm.description.as_deref(), Some("TPM 2.0 Device"));
+        // MSFT0101 is the generic TPM 2.0 ID, and the fixture has no device to ask
+        assert_eq!(tpm.manufacturer, None);
+    }
+
+    #[test]
+    fn test_uefi_in_setup_mode() {
+        let fw = collect(&fixture_root("setup_mode")).unwrap().unwrap();
+        assert_eq!(fw.secure_boot, Some(false));
+        assert_eq!(fw.setup_mode, Some(true));
+        assert!(fw.tpm.is_none());
+    }
+
+    #[test]
+    fn test_legacy_boot_with_tpm12() {
+        let fw = collect(&fixture_root("legacy")).unwrap().unwrap();
+        assert_eq!(fw.boot_mode, BootMode::Legacy);
+        assert_eq!(fw.secure_boot, None);
+
+        let tpm = fw.tpm.unwrap();
+        assert_eq!(tpm.version.as_deref(), Some("1.2"));
+        assert_eq!(tpm.manufacturer.as_deref(), Some("Infineon"));
+    }
+
+    #[test]
+    fn test_tpm2_devicetree_vendor() {
+        let fw = collect(&fixture_root("tpm2_spi")).unwrap().unwrap();
+        let tpm = fw.tpm.unwrap();
+        assert_eq!(tpm.version.as_deref(), Some("2.0"));
+        assert_eq!(tpm.manufacturer.as_deref(), Some("Infineon"));
+    }
+
+    #[test]
+    fn test_tpm2_get_capability() {
+        // Replays an Intel PTT response and records the command
+        struct FakeTpm {
+            command: Vec<u8>,
+            response: std::io::Cursor<Vec<u8>>,
+        }
+        impl Read for FakeTpm {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.response.read(buf)
+            }
+        }
+        impl Write for FakeTpm {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.command.write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let response = vec![
+            0x80, 0x01, 0x00, 0x00, 0x00, 0x1b, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x05, 0x49, 0x4e, 0x54, 0x43,
+        ];
+        let mut tpm = FakeTpm {
+            command: Vec::new(),
+            response: std::io::Cursor::new(response.clone()),
+        };
+        assert_eq!(get_tpm2_manufacturer(&mut tpm), Some("Intel"));
+        assert_eq!(tpm.command, TPM2_GET_MANUFACTURER);
+
+        // TPM_RC_INITIALIZE and truncated responses
+        let mut failed = response.clone();
+        failed[9] = 0x00;
+        failed[8] = 0x01;
+        assert_eq!(parse_tpm2_manufacturer(&failed), None);
+        assert_eq!(parse_tpm2_manufacturer(&response[..20]), None);
+    }
+
+    #[test]
+    fn test_vendor_lookup() {
+        assert_eq!(vendor_from_id("0x4E544300"), Some("Nuvoton"));
+        assert_eq!(vendor_from_hid("IFX0102"), Some("Infineon"));
+        assert_eq!(vendor_from_hid("MSFT0101"), None);
+        assert_eq!(
+            vendor_from_compatible(b"nuvoton,npct75x\0tcg,tpm-tis-i2c\0"),
+            Some("Nuvoton")
+        );
+        assert_eq!(vendor_from_compatible(b"tcg,tpm_tis-spi\0"), None);
+    }
+
+    #[test]
+    fn test_no_sysfs() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
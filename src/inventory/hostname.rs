@@ -0,0 +1,272 @@
+//! Hostname, fully qualified name, DNS domain and directory (AD, FreeIPA, LDAP) membership.
+//!
+//! The hostname comes from the first source in a fixed chain: the caller's candidates (the
+//! environment and WMI on Windows), `/etc/hostname`, `/proc/sys/kernel/hostname` and finally the
+//! caller's fallback. Directory membership is read from SSSD and Samba configuration.
+
+use std::path::Path;
+
+use super::read_attr;
+use super::systemd::{parse_unit, unit_value};
+use crate::models::{DirectoryKind, DirectoryMembership, HostIdentity, HostnameSource};
+
+const SSSD_CONF: &str = "etc/sssd/sssd.conf";
+const SMB_CONF: &str = "etc/samba/smb.conf";
+const KRB5_CONF: &str = "etc/krb5.conf";
+const KRB5_KEYTAB: &str = "etc/krb5.keytab";
+
+/// Resolve the host identity. `candidates` are tried in order before the files under `root`;
+/// `fallback` (normally the primary IP address) is used when no source yields a name.
+pub fn collect(
+    root: &Path,
+    candidates: &[(HostnameSource, Option<String>)],
+    fallback: &str,
+) -> HostIdentity {
+    let file_sources = [
+        (HostnameSource::EtcHostname, read_hostname_file(root)),
+        (
+            HostnameSource::KernelHostname,
+            read_attr(&root.join("proc/sys/kernel"), "hostname"),
+        ),
+    ];
+    let (source, name) = candidates
+        .iter()
+        .chain(file_sources.iter())
+        .find_map(|(source, name)| {
+            let name = name.as_deref()?.trim();
+            (!name.is_empty() && name != "localhost").then(|| (*source, name.to_string()))
+        })
+        .unwrap_or_else(|| (HostnameSource::Fallback, fallback.to_string()));
+
+    // Keep the short name; a configured FQDN is the best FQDN source there is
+    let (hostname, configured_fqdn) = match name.split_once('.') {
+        Some((short, _)) if source != HostnameSource::Fallback => {
+            (short.to_string(), Some(name.clone()))
+        }
+        _ => (name, None),
+    };
+
+    let directory = read_sssd(root).or_else(|| read_winbind(root));
+    // An IP address fallback has no name to qualify
+    let fqdn = (source != HostnameSource::Fallback)
+        .then(|| {
+            configured_fqdn
+                .or_else(|| fqdn_from_hosts(root, &hostname))
+                .or_else(|| {
+                    let domain =
+                        resolv_domain(root).or_else(|| directory.as_ref()?.domain.clone())?;
+                    Some(format!("{}.{}", hostname, domain))
+                })
+        })
+        .flatten()
+        .map(|f| f.to_lowercase());
+
+    HostIdentity {
+        dns_domain: fqdn
+            .as_deref()
+            .and_then(|f| f.split_once('.'))
+            .map(|(_, d)| d.to_string()),
+        hostname,
+        hostname_source: source,
+        fqdn,
+        kerberos_realm: std::fs::read_to_string(root.join(KRB5_CONF))
+            .ok()
+            .and_then(|text| {
+                unit_value(&parse_unit(&text), "libdefaults", "default_realm").map(str::to_string)
+            }),
+        directory,
+    }
+}
+
+/// Record Windows domain membership as reported by `Win32_ComputerSystem`, unless the files
+/// already described one.
+pub fn apply_windows_domain(identity: &mut HostIdentity, domain: &str) {
+    let domain = domain.to_lowercase();
+    if identity.fqdn.is_none() {
+        identity.fqdn = Some(format!("{}.{}", identity.hostname.to_lowercase(), domain));
+        identity.dns_domain = Some(domain.clone());
+    }
+    identity.directory.get_or_insert(DirectoryMembership {
+        kind: DirectoryKind::ActiveDirectory,
+        client: "windows".to_string(),
+        realm: Some(domain.to_uppercase()),
+        domain: Some(domain),
+        machine_credentials: true,
+    });
+}
+
+fn read_hostname_file(root: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(root.join("etc/hostname")).ok()?;
+    text.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+}
+
+/// First dotted name on an `/etc/hosts` line that also lists `hostname`, e.g.
+/// `127.0.1.1 ws042.corp.example.com ws042`.
+fn fqdn_from_hosts(root: &Path, hostname: &str) -> Option<String> {
+    let hosts = std::fs::read_to_string(root.join("etc/hosts")).ok()?;
+    let prefix = format!("{}.", hostname.to_lowercase());
+    hosts.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let names: Vec<String> = line
+            .split_whitespace()
+            .skip(1)
+            .map(str::to_lowercase)
+            .collect();
+        if !names.iter().any(|n| *n == hostname.to_lowercase()) {
+            return None;
+        }
+        names.into_iter().find(|n| n.starts_with(&prefix))
+    })
+}
+
+/// The `domain` directive of resolv.conf; `search` lists are not specific enough to name the
+/// host's own domain.
+fn resolv_domain(root: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(root.join("etc/resolv.conf")).ok()?;
+    text.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next()? == "domain").then(|| fields.next().map(str::to_string))?
+    })
+}
+
+fn read_sssd(root: &Path) -> Option<DirectoryMembership> {
+    let text = std::fs::read_to_string(root.join(SSSD_CONF)).ok()?;
+    let entries = parse_unit(&text);
+    // The first domain in [sssd] domains, else the first [domain/...] section
+    let name = unit_value(&entries, "sssd", "domains")
+        .and_then(|d| d.split(',').map(str::trim).find(|d| !d.is_empty()))
+        .map(str::to_string)
+        .or_else(|| {
+            entries
+                .iter()
+                .find_map(|(section, _, _)| section.strip_prefix("domain/").map(str::to_string))
+        })?;
+    let section = format!("domain/{}", name);
+    let value = |key: &str| unit_value(&entries, &section, key).map(str::to_string);
+
+    let kind = match value("id_provider").as_deref() {
+        Some("ad") => DirectoryKind::ActiveDirectory,
+        Some("ipa") => DirectoryKind::Ipa,
+        Some("ldap") => DirectoryKind::Ldap,
+        _ => DirectoryKind::Other,
+    };
+    let domain = value("ad_domain")
+        .or_else(|| value("ipa_domain"))
+        .unwrap_or(name)
+        .to_lowercase();
+    Some(DirectoryMembership {
+        kind,
+        client: "sssd".to_string(),
+        realm: value("krb5_realm").or_else(|| Some(domain.to_uppercase())),
+        domain: Some(domain),
+        machine_credentials: root.join(KRB5_KEYTAB).is_file(),
+    })
+}
+
+fn read_winbind(root: &Path) -> Option<DirectoryMembership> {
+    let text = std::fs::read_to_string(root.join(SMB_CONF)).ok()?;
+    let entries = parse_unit(&text);
+    let value = |key: &str| unit_value(&entries, "global", key);
+    if !value("security").is_some_and(|s| s.eq_ignore_ascii_case("ads")) {
+        return None;
+    }
+    let realm = value("realm").map(str::to_uppercase);
+    Some(DirectoryMembership {
+        kind: DirectoryKind::ActiveDirectory,
+        client: "winbind".to_string(),
+        domain: realm.as_deref().map(str::to_lowercase),
+        realm,
+        machine_credentials: root.join(KRB5_KEYTAB).is_file()
+            || root.join("var/lib/samba/private/secrets.tdb").is_file(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/hostname")
+            .join(name)
+    }
+
+    #[test]
+    fn test_sssd_ad_member() {
+        let id = collect(&fixture("sssd_ad"), &[], "10.0.0.5");
+        assert_eq!(id.hostname, "ws042");
+        assert_eq!(id.hostname_source, HostnameSource::EtcHostname);
+        assert_eq!(id.fqdn.as_deref(), Some("ws042.corp.example.com"));
+        assert_eq!(id.dns_domain.as_deref(), Some("corp.example.com"));
+        assert_eq!(id.kerberos_realm.as_deref(), Some("CORP.EXAMPLE.COM"));
+
+        let dir = id.directory.unwrap();
+        assert_eq!(dir.kind, DirectoryKind::ActiveDirectory);
+        assert_eq!(dir.client, "sssd");
+        assert_eq!(dir.domain.as_deref(), Some("corp.example.com"));
+        assert_eq!(dir.realm.as_deref(), Some("CORP.EXAMPLE.COM"));
+        assert!(dir.machine_credentials);
+    }
+
+    #[test]
+    fn test_winbind_with_fqdn_hostname_file() {
+        let id = collect(&fixture("winbind"), &[], "10.0.0.5");
+        assert_eq!(id.hostname, "build7");
+        assert_eq!(id.fqdn.as_deref(), Some("build7.lab.example.org"));
+        assert_eq!(id.dns_domain.as_deref(), Some("lab.example.org"));
+
+        let dir = id.directory.unwrap();
+        assert_eq!(dir.client, "winbind");
+        assert_eq!(dir.realm.as_deref(), Some("LAB.EXAMPLE.ORG"));
+        assert!(!dir.machine_credentials);
+    }
+
+    #[test]
+    fn test_kernel_hostname_and_resolv_domain() {
+        let id = collect(&fixture("standalone"), &[], "10.0.0.5");
+        assert_eq!(id.hostname, "kiosk-3");
+        assert_eq!(id.hostname_source, HostnameSource::KernelHostname);
+        assert_eq!(id.fqdn.as_deref(), Some("kiosk-3.branch.example.net"));
+        assert!(id.directory.is_none());
+        assert_eq!(id.kerberos_realm, None);
+    }
+
+    #[test]
+    fn test_candidates_take_precedence() {
+        let candidates = [
+            (HostnameSource::Environment, None),
+            (HostnameSource::Wmi, Some("LAPTOP-ABC123".to_string())),
+        ];
+        let id = collect(&fixture("sssd_ad"), &candidates, "10.0.0.5");
+        assert_eq!(id.hostname, "LAPTOP-ABC123");
+        assert_eq!(id.hostname_source, HostnameSource::Wmi);
+    }
+
+    #[test]
+    fn test_fallback() {
+        let id = collect(Path::new("/nonexistent"), &[], "10.0.0.5");
+        assert_eq!(id.hostname, "10.0.0.5");
+        assert_eq!(id.hostname_source, HostnameSource::Fallback);
+        assert_eq!(id.fqdn, None);
+        assert_eq!(id.dns_domain, None);
+    }
+
+    #[test]
+    fn test_apply_windows_domain() {
+        let candidates = [(
+            HostnameSource::Environment,
+            Some("LAPTOP-ABC123".to_string()),
+        )];
+        let mut id = collect(Path::new("/nonexistent"), &candidates, "10.0.0.5");
+        apply_windows_domain(&mut id, "CORP.example.com");
+        assert_eq!(id.fqdn.as_deref(), Some("laptop-abc123.corp.example.com"));
+        assert_eq!(id.dns_domain.as_deref(), Some("corp.example.com"));
+        let dir = id.directory.unwrap();
+        assert_eq!(dir.client, "windows");
+        assert_eq!(dir.realm.as_deref(), Some("CORP.EXAMPLE.COM"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Login, logout, reboot and shutdown history from wtmp.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+
+use super::utmp::{self, Record, BOOT_TIME, DEAD_PROCESS, RUN_LVL, USER_PROCESS};
+use crate::config::LoginHistoryConfig;
+use crate::models::{LoginEvent, LoginEventType, LoginHistory};
+
+/// wtmp and its logrotate predecessor, oldest first.
+const WTMP_PATHS: &[&str] = &["var/log/wtmp.1", "var/log/wtmp"];
+
+/// Records read from the end of each file, about 7.5 MiB. wtmp is only rotated monthly by
+/// default and grows by a few records per login, so busy servers accumulate large files.
+const MAX_WTMP_RECORDS: usize = 20_000;
+
+/// Build the history of events after `since` plus the most frequent user summary.
+///
+/// Without a previous check-in (`since` is `None`), events from the summary window are used.
+pub fn collect(
+    root: &Path,
+    cfg: &LoginHistoryConfig,
+    since: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Option<LoginHistory>> {
+    let mut records = Vec::new();
+    let mut found = false;
+    let mut cut = false;
+    for path in WTMP_PATHS.iter().map(|p| root.join(p)) {
+        if !path.is_file() {
+            continue;
+        }
+        found = true;
+        let (data, file_cut) = read_tail(&path, MAX_WTMP_RECORDS)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if file_cut {
+            // Older files end before the records that were skipped
+            records.clear();
+            cut = true;
+        }
+        records.extend(utmp::parse(&data));
+    }
+    if !found {
+        return Ok(None);
+    }
+
+    let window_start = now - Duration::days(cfg.frequent_user_days as i64);
+    let mut events = events(&records);
+    let since = since.unwrap_or(window_start);
+    // Events between `since` and the oldest record read were lost
+    let mut truncated = cut
+        && records
+            .iter()
+            .find_map(|r| r.time)
+            .is_some_and(|t| t > since);
+    events.retain(|(time, _)| *time > since);
+
+    // Keep the most recent events when over the limit
+    truncated |= events.len() > cfg.max_events;
+    if truncated {
+        events.drain(..events.len() - cfg.max_events);
+    }
+
+    Ok(Some(LoginHistory {
+        events: events.into_iter().map(|(_, e)| e).collect(),
+        truncated,
+        most_frequent_user: most_frequent_user(&records, window_start),
+        frequent_user_days: cfg.frequent_user_days,
+    }))
+}
+
+/// Read at most the last `max_records` complete records of `path`. Also returns whether earlier
+/// records were skipped.
+fn read_tail(path: &Path, max_records: usize) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut file = File::open(path)?;
+    let record_len = utmp::RECORD_LEN as u64;
+    let skip = (file.metadata()?.len() / record_len).saturating_sub(max_records as u64);
+    file.seek(SeekFrom::Start(skip * record_len))?;
+    let mut data = Vec::new();
+    file.take(max_records as u64 * record_len)
+        .read_to_end(&mut data)?;
+    Ok((data, skip > 0))
+}
+
+/// Translate records into events, in file order. Logouts are attributed to the user whose login
+/// opened the same terminal, since DEAD_PROCESS records carry no user name.
+fn events(records: &[Record]) -> Vec<(DateTime<Utc>, LoginEvent)> {
+    let mut open: HashMap<&str, &Record> = HashMap::new();
+    let mut events = Vec::new();
+
+    for record in records {
+        let Some(time) = record.time else {
+            continue;
+        };
+        let event = match record.kind {
+            USER_PROCESS if !record.user.is_empty() => {
+                open.insert(&record.line, record);
+                LoginEvent {
+                    event: LoginEventType::Login,
+                    user: Some(record.user.clone()),
+                    terminal: Some(record.line.clone()).filter(|l| !l.is_empty()),
+                    source_host: source_host(record),
+                    time: time.to_rfc3339(),
+                }
+            }
+            DEAD_PROCESS if !record.line.is_empty() => {
+                let Some(login) = open.remove(record.line.as_str()) else {
+                    continue;
+                };
+                LoginEvent {
+                    event: LoginEventType::Logout,
+                    user: Some(login.user.clone()),
+                    terminal: Some(record.line.clone()),
+                    source_host: source_host(login),
+                    time: time.to_rfc3339(),
+                }
+            }
+            BOOT_TIME => {
+                open.clear();
+                system_event(LoginEventType::Reboot, time)
+            }
+            RUN_LVL if record.user == "shutdown" => system_event(LoginEventType::Shutdown, time),
+            _ => continue,
+        };
+        events.push((time, event));
+    }
+    events
+}
+
+fn system_event(event: LoginEventType, time: DateTime<Utc>) -> LoginEvent {
+    LoginEvent {
+        event,
+        user: None,
+        terminal: None,
+        source_host: None,
+        time: time.to_rfc3339(),
+    }
+}
+
+/// Remote host of a login; display names such as ":0" are local.
+fn source_host(record: &Record) -> Option<String> {
+    if record.host.is_empty() || record.host.starts_with(':') {
+        return record.addr.map(|a| a.to_string());
+    }
+    Some(record.host.clone())
+}
+
+/// User with the most logins since `window_start`; ties go to the most recent login.
+fn most_frequent_user(records: &[Record], window_start: DateTime<Utc>) -> Option<String> {
+    let mut counts: HashMap<&str, (usize, DateTime<Utc>)> = HashMap::new();
+    for record in records {
+        let Some(time) = record.time else {
+            continue;
+        };
+        if record.kind != USER_PROCESS || record.user.is_empty() || time < window_start {
+            continue;
+        }
+        let entry = counts.entry(&record.user).or_insert((0, time));
+        entry.0 += 1;
+        entry.1 = entry.1.max(time);
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, (count, last))| (*count, *last))
+        .map(|(user, _)| user.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::utmp::tests::record;
+    use chrono::TimeZone;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/login_history")
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 10, day, hour, 0, 0).unwrap()
+    }
+
+    fn kinds(history: &LoginHistory) -> Vec<LoginEventType> {
+        history.events.iter().map(|e| e.event).collect()
+    }
+
+    #[test]
+    fn test_events_since_last_checkin() {
+        // wtmp.1 holds September; wtmp holds October
+        let history = collect(
+            &fixture_root(),
+            &LoginHistoryConfig::default(),
+            Some(at(8, 12)),
+            at(9, 18),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            kinds(&history),
+            vec![
+                LoginEventType::Shutdown,
+                LoginEventType::Reboot,
+                LoginEventType::Login,
+                LoginEventType::Login,
+                LoginEventType::Logout,
+            ]
+        );
+        assert!(!history.truncated);
+
+        let ssh_logout = &history.events[4];
+        assert_eq!(ssh_logout.user.as_deref(), Some("bob"));
+        assert_eq!(ssh_logout.terminal.as_deref(), Some("pts/0"));
+        assert_eq!(ssh_logout.source_host.as_deref(), Some("10.20.30.40"));
+        assert_eq!(ssh_logout.time, "2025-10-09T10:30:00+00:00");
+
+        // alice logs in daily; bob only once
+        assert_eq!(history.most_frequent_user.as_deref(), Some("alice"));
+        assert_eq!(history.frequent_user_days, 30);
+    }
+
+    #[test]
+    fn test_first_checkin_uses_summary_window_and_limit() {
+        let cfg = LoginHistoryConfig {
+            max_events: 3,
+            frequent_user_days: 7,
+        };
+        let history = collect(&fixture_root(), &cfg, None, at(9, 18))
+            .unwrap()
+            .unwrap();
+        assert!(history.truncated);
+        assert_eq!(history.events.len(), 3);
+        assert_eq!(history.events[2].event, LoginEventType::Logout);
+    }
+
+    #[test]
+    fn test_most_frequent_user_tie_goes_to_latest() {
+        let mut data = record(USER_PROCESS, 1, "tty1", "alice", "", at(1, 9).timestamp());
+        data.extend(record(
+            USER_PROCESS,
+            2,
+            "tty1",
+            "bob",
+            "",
+            at(2, 9).timestamp(),
+        ));
+        let records = utmp::parse(&data);
+        assert_eq!(
+            most_frequent_user(&records, at(1, 0)).as_deref(),
+            Some("bob")
+        );
+        assert_eq!(most_frequent_user(&records, at(3, 0)), None);
+    }
+
+    #[test]
+    fn test_read_tail() {
+        let mut data = Vec::new();
+        for (pid, user) in ["alice", "bob", "carol"].iter().enumerate() {
+            data.extend(record(
+                USER_PROCESS,
+                pid as i32,
+                "tty1",
+                user,
+                "",
+                at(1, 9).timestamp(),
+            ));
+        }
+        // Partial record being appended
+        data.extend([0u8; 10]);
+        let path =
+            std::env::temp_dir().join(format!("inventory-agent-wtmp-{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+
+        let (tail, cut) = read_tail(&path, 2).unwrap();
+        let users: Vec<_> = utmp::parse(&tail).into_iter().map(|r| r.user).collect();
+        assert_eq!(users, vec!["bob", "carol"]);
+        assert!(cut);
+
+        let (all, cut) = read_tail(&path, 3).unwrap();
+        assert_eq!(utmp::parse(&all).len(), 3);
+        assert!(!cut);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_wtmp() {
+        let history = collect(
+            Path::new("/nonexistent"),
+            &LoginHistoryConfig::default(),
+            None,
+            Utc::now(),
+        )
+        .unwrap();
+        assert!(history.is_none());
+    }
+}
@@ -0,0 +1,89 @@
+//! Inventory sections read from operating system files.
+//!
+//! Unlike `collector`, these modules are not tied to WMI. Each one reads its data from files under
+//! a root directory (normally `/`), which keeps the parsers testable against fixture trees on any
+//! platform. A section whose source files are absent simply comes back empty.
+
+pub mod accounts;
+pub mod battery;
+pub mod bluetooth;
+pub mod cpu;
+pub mod dmi;
+pub mod encryption;
+pub mod firewall;
+pub mod firmware;
+pub mod hostname;
+pub mod login_history;
+pub mod monitors;
+pub mod network;
+pub mod os_release;
+pub mod patches;
+pub mod pci;
+pub mod processes;
+pub mod security_products;
+pub mod services;
+pub mod sessions;
+pub mod sockets;
+pub mod software;
+pub(crate) mod sqlite;
+pub(crate) mod systemd;
+pub mod usb;
+pub(crate) mod utmp;
+pub mod virtualization;
+
+use std::path::Path;
+
+use anyhow::Result;
+
+/// Unwrap a section result, logging and falling back to an empty section on failure.
+///
+/// A broken section must not prevent the rest of the check-in from being sent.
+pub fn best_effort<T: Default>(section: &str, result: Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        log::warn!("{} inventory failed: {:#}", section, e);
+        T::default()
+    })
+}
+
+/// Read a sysfs-style attribute file, trimmed, treating empty values as absent.
+pub(crate) fn read_attr(dir: &Path, name: &str) -> Option<String> {
+    let value = std::fs::read_to_string(dir.join(name)).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Match `text` against a pattern in which `*` matches any run of characters.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("sshd", "sshd"));
+        assert!(!glob_match("sshd", "sshd2"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("a*c*e", "abcde"));
+        assert!(!glob_match("a*c*e", "abcd"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Connected monitors from EDID blobs under `/sys/class/drm`.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use super::read_attr;
+use crate::models::Monitor;
+
+const DRM: &str = "sys/class/drm";
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const EDID_BLOCK_LEN: usize = 128;
+
+// Display descriptor tags
+const TAG_SERIAL: u8 = 0xff;
+const TAG_TEXT: u8 = 0xfe;
+const TAG_NAME: u8 = 0xfc;
+
+/// Read every connected DRM connector that exposes an EDID.
+pub fn collect(root: &Path) -> Result<Vec<Monitor>> {
+    let dir = root.join(DRM);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut connectors = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to list {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.join("edid").is_file())
+        .collect::<Vec<_>>();
+    connectors.sort();
+
+    let mut monitors = Vec::new();
+    for path in connectors {
+        if read_attr(&path, "status").as_deref() != Some("connected") {
+            continue;
+        }
+        let blob = match std::fs::read(path.join("edid")) {
+            Ok(blob) => blob,
+            Err(e) => {
+                log::warn!("skipping {}: {}", path.join("edid").display(), e);
+                continue;
+            }
+        };
+        // Connected but not yet probed connectors expose an empty blob
+        if blob.is_empty() {
+            continue;
+        }
+        let connector = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match parse_edid(&blob) {
+            Ok(monitor) => monitors.push(Monitor {
+                connector,
+                ..monitor
+            }),
+            Err(e) => log::warn!("skipping EDID for {}: {:#}", connector, e),
+        }
+    }
+    Ok(monitors)
+}
+
+/// Parse the 128-byte EDID base block; extension blocks are ignored.
+///
+/// The returned monitor has an empty `connector`, which only the caller knows.
+pub fn parse_edid(blob: &[u8]) -> Result<Monitor> {
+    let Some(block) = blob.get(..EDID_BLOCK_LEN) else {
+        bail!("EDID shorter than {} bytes", EDID_BLOCK_LEN);
+    };
+    if block[..8] != EDID_HEADER {
+        bail!("missing EDID header");
+    }
+    if block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) != 0 {
+        bail!("EDID checksum mismatch");
+    }
+
+    // Three 5-bit letters, 1 = 'A', packed big-endian
+    let packed = u16::from_be_bytes([block[8], block[9]]);
+    let manufacturer_id = [10, 5, 0]
+        .iter()
+        .map(|shift| (b'A' - 1 + ((packed >> shift) & 0x1f) as u8) as char)
+        .collect();
+    let product_code = u16::from_le_bytes([block[10], block[11]]);
+    let numeric_serial = u32::from_le_bytes([block[12], block[13], block[14], block[15]]);
+
+    // Week 0 means unspecified and 0xff marks the year as a model year
+    let manufacture_week = match block[16] {
+        0 | 0xff => None,
+        w => Some(w),
+    };
+    let manufacture_year = 1990 + block[17] as u16;
+    let width_cm = (block[21] != 0).then_some(block[21]);
+    let height_cm = (block[22] != 0).then_some(block[22]);
+
+    let mut model_name = None;
+    let mut serial_string = None;
+    let mut last_text = None;
+    let mut native = None;
+
+    for desc in block[54..126].chunks_exact(18) {
+        let pixel_clock = u16::from_le_bytes([desc[0], desc[1]]);
+        if pixel_clock != 0 {
+            // The first detailed timing descriptor is the preferred (native) mode
+            if native.is_none() {
+                let width = desc[2] as u32 | ((desc[4] as u32 & 0xf0) << 4);
+                let height = desc[5] as u32 | ((desc[7] as u32 & 0xf0) << 4);
+                native = Some((width, height));
+            }
+            continue;
+        }
+        match desc[3] {
+            TAG_NAME => model_name = descriptor_text(desc),
+            TAG_SERIAL => serial_string = descriptor_text(desc),
+            TAG_TEXT => last_text = descriptor_text(desc).or(last_text),
+            _ => {}
+        }
+    }
+
+    Ok(Monitor {
+        connector: String::new(),
+        manufacturer_id,
+        product_code,
+        serial_number: serial_string
+            .or_else(|| (numeric_serial != 0).then(|| numeric_serial.to_string())),
+        // Laptop panels usually carry the part number as unspecified text instead of a name
+        model_name: model_name.or(last_text),
+        manufacture_week,
+        manufacture_year,
+        native_width: native.map(|n| n.0),
+        native_height: native.map(|n| n.1),
+        width_cm,
+        height_cm,
+    })
+}
+
+/// Decode the 13-byte text payload of a display descriptor, terminated by a line feed.
+fn descriptor_text(desc: &[u8]) -> Option<String> {
+    let text = &desc[5..18];
+    let end = text.iter().position(|b| *b == 0x0a).unwrap_or(text.len());
+    let s = String::from_utf8_lossy(&text[..end]).trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/monitors")
+    }
+
+    fn edid(connector: &str) -> Vec<u8> {
+        std::fs::read(
+            fixture_root()
+                .join("sys/class/drm")
+                .join(connector)
+                .join("edid"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_external_monitor() {
+        let monitor = parse_edid(&edid("card0-DP-1")).unwrap();
+        assert_eq!(monitor.manufacturer_id, "DEL");
+        assert_eq!(monitor.product_code, 0xa0f5);
+        assert_eq!(monitor.model_name.as_deref(), Some("DELL U2720Q"));
+        // The serial string descriptor wins over the numeric serial
+        assert_eq!(monitor.serial_number.as_deref(), Some("FN84K83"));
+        assert_eq!(monitor.manufacture_week, Some(12));
+        assert_eq!(monitor.manufacture_year, 2021);
+        assert_eq!(
+            (monitor.native_width, monitor.native_height),
+            (Some(3840), Some(2160))
+        );
+        assert_eq!((monitor.width_cm, monitor.height_cm), (Some(60), Some(34)));
+    }
+
+    #[test]
+    fn test_parse_laptop_panel() {
+        let monitor = parse_edid(&edid("card0-eDP-1")).unwrap();
+        assert_eq!(monitor.manufacturer_id, "BOE");
+        assert_eq!(monitor.model_name.as_deref(), Some("NV140FHM-N49"));
+        assert_eq!(monitor.serial_number, None);
+        assert_eq!(monitor.manufacture_week, None);
+        assert_eq!(monitor.manufacture_year, 2020);
+        assert_eq!(
+            (monitor.native_width, monitor.native_height),
+            (Some(1920), Some(1080))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_corrupt_blobs() {
+        let mut blob = edid("card0-DP-1");
+        blob[20] ^= 0x01;
+        assert!(parse_edid(&blob).is_err(), "checksum mismatch");
+        assert!(parse_edid(&blob[..64]).is_err(), "truncated");
+        assert!(parse_edid(&[0u8; 128]).is_err(), "no header");
+    }
+
+    #[test]
+    fn test_collect_connected_monitors() {
+        let monitors = collect(&fixture_root()).unwrap();
+        // HDMI is disconnected and DP-2 is connected without an EDID
+        let connectors: Vec<_> = monitors.iter().map(|m| m.connector.as_str()).collect();
+        assert_eq!(connectors, vec!["card0-DP-1", "card0-eDP-1"]);
+        assert_eq!(monitors[0].serial_number.as_deref(), Some("FN84K83"));
+    }
+}
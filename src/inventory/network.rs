@@ -0,0 +1,304 @@
+//! Default gateways, DNS resolvers and active VPN interfaces.
+//!
+//! Gateways come from `/proc/net/route` and `/proc/net/ipv6_route`, resolvers from
+//! `/etc/resolv.conf`. When that points at the systemd-resolved stub listener, the upstream
+//! servers are read from the file systemd-resolved maintains instead.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::read_attr;
+use crate::models::{Gateway, NetworkConfiguration, VpnInterface};
+
+const RESOLV_CONF: &str = "etc/resolv.conf";
+/// Upstream servers as configured in systemd-resolved, in resolv.conf syntax.
+const RESOLVED_UPSTREAM: &str = "run/systemd/resolve/resolv.conf";
+/// Addresses of the systemd-resolved stub listeners.
+const RESOLVED_STUBS: &[&str] = &["127.0.0.53", "127.0.0.54"];
+
+const NET_CLASS: &str = "sys/class/net";
+
+const RTF_UP: u32 = 0x1;
+const RTF_GATEWAY: u32 = 0x2;
+const IFF_UP: u32 = 0x1;
+
+/// Interface name prefixes of VPN software and the kind they indicate.
+const VPN_PREFIXES: &[(&str, &str)] = &[
+    ("wg", "wireguard"),
+    ("nordlynx", "wireguard"),
+    ("tailscale", "tailscale"),
+    ("zt", "zerotier"),
+    ("cscotun", "anyconnect"),
+    ("gpd", "globalprotect"),
+    ("tun", "tun"),
+    ("ppp", "ppp"),
+    ("ipsec", "ipsec"),
+    ("vti", "ipsec"),
+    ("xfrm", "ipsec"),
+];
+
+/// Read the network configuration. Returns `None` where there is neither a routing table nor a
+/// resolv.conf (non-Linux hosts).
+pub fn collect(root: &Path) -> Result<Option<NetworkConfiguration>> {
+    let route = std::fs::read_to_string(root.join("proc/net/route")).ok();
+    let resolv = std::fs::read_to_string(root.join(RESOLV_CONF)).ok();
+    if route.is_none() && resolv.is_none() {
+        return Ok(None);
+    }
+
+    let mut default_gateways = route.as_deref().map(parse_ipv4_routes).unwrap_or_default();
+    if let Ok(table) = std::fs::read_to_string(root.join("proc/net/ipv6_route")) {
+        default_gateways.extend(parse_ipv6_routes(&table));
+    }
+    default_gateways.sort_by(|a, b| {
+        (a.metric, &a.interface, &a.address).cmp(&(b.metric, &b.interface, &b.address))
+    });
+    // Both halves of a split default route name the same interface and next hop
+    default_gateways.dedup_by(|a, b| {
+        a.metric == b.metric && a.interface == b.interface && a.address == b.address
+    });
+
+    let (mut dns_servers, mut search_domains) =
+        resolv.as_deref().map(parse_resolv_conf).unwrap_or_default();
+    let resolved_stub = !dns_servers.is_empty()
+        && dns_servers
+            .iter()
+            .all(|s| RESOLVED_STUBS.contains(&s.as_str()));
+    if resolved_stub {
+        if let Ok(upstream) = std::fs::read_to_string(root.join(RESOLVED_UPSTREAM)) {
+            (dns_servers, search_domains) = parse_resolv_conf(&upstream);
+        }
+    }
+
+    Ok(Some(NetworkConfiguration {
+        default_gateways,
+        dns_servers,
+        search_domains,
+        systemd_resolved_stub: resolved_stub,
+        vpn_interfaces: read_vpn_interfaces(root),
+    }))
+}
+
+/// Default routes from `/proc/net/route`, whose addresses are hex in host byte order.
+///
+/// VPN clients such as OpenVPN's `redirect-gateway def1` override the default route with
+/// `0.0.0.0/1` and `128.0.0.0/1`, which are reported as defaults too. Routes straight onto a
+/// point-to-point link (WireGuard, PPP) have no gateway address.
+pub fn parse_ipv4_routes(table: &str) -> Vec<Gateway> {
+    let half = Ipv4Addr::new(128, 0, 0, 0);
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let hex = |i: usize| u32::from_str_radix(fields.get(i)?, 16).ok();
+            let addr = |i: usize| hex(i).map(|v| Ipv4Addr::from(v.to_ne_bytes()));
+            let (destination, gateway, flags, metric, mask) = (
+                addr(1)?,
+                addr(2)?,
+                hex(3)?,
+                fields.get(6)?.parse().ok()?,
+                addr(7)?,
+            );
+            let is_default = (destination.is_unspecified() && mask.is_unspecified())
+                || (mask == half && (destination.is_unspecified() || destination == half));
+            (is_default && flags & RTF_UP != 0).then(|| Gateway {
+                interface: fields[0].to_string(),
+                address: (flags & RTF_GATEWAY != 0).then(|| gateway.to_string()),
+                metric,
+            })
+        })
+        .collect()
+}
+
+/// Default routes from `/proc/net/ipv6_route`: destination, prefix length, source, source
+/// prefix length, next hop, metric, reference count, use count, flags and interface. As for
+/// IPv4, `::/1` and `8000::/1` count as defaults.
+pub fn parse_ipv6_routes(table: &str) -> Vec<Gateway> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [destination, prefix_len, _, _, next_hop, metric, _, _, flags, interface] =
+                fields[..]
+            else {
+                return None;
+            };
+            let flags = u32::from_str_radix(flags, 16).ok()?;
+            let destination = u128::from_str_radix(destination, 16).ok()?;
+            let is_default = match prefix_len {
+                "00" => destination == 0,
+                "01" => destination == 0 || destination == 1 << 127,
+                _ => false,
+            };
+            if !is_default || flags & RTF_UP == 0 {
+                return None;
+            }
+            let next_hop = Ipv6Addr::from(u128::from_str_radix(next_hop, 16).ok()?);
+            Some(Gateway {
+                interface: interface.to_string(),
+                address: (flags & RTF_GATEWAY != 0).then(|| next_hop.to_string()),
+                metric: u32::from_str_radix(metric, 16).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Name servers and search domains. As in glibc, the last `search` or `domain` line wins.
+pub fn parse_resolv_conf(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut servers = Vec::new();
+    let mut search = Vec::new();
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("nameserver") => servers.extend(fields.next().map(str::to_string)),
+            Some("search") | Some("domain") => {
+                search = fields
+                    .take_while(|f| !f.starts_with('#') && !f.starts_with(';'))
+                    .map(str::to_string)
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    (servers, search)
+}
+
+/// Interfaces that are up and look like VPN tunnels, by driver or by name.
+fn read_vpn_interfaces(root: &Path) -> Vec<VpnInterface> {
+    let Ok(entries) = std::fs::read_dir(root.join(NET_CLASS)) else {
+        return Vec::new();
+    };
+    let mut interfaces: Vec<VpnInterface> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let dir = entry.path();
+            let devtype = std::fs::read_to_string(dir.join("uevent"))
+                .ok()
+                .and_then(|u| {
+                    u.lines()
+                        .find_map(|l| l.strip_prefix("DEVTYPE=").map(str::to_string))
+                });
+            // Custom names (OpenVPN "corp0", WireGuard "office") are recognized by driver
+            let kind = match devtype.as_deref() {
+                Some("wireguard") => "wireguard".to_string(),
+                Some("ppp") => "ppp".to_string(),
+                _ => VPN_PREFIXES
+                    .iter()
+                    .find(|(prefix, _)| name.starts_with(prefix))
+                    .map(|(_, kind)| kind.to_string())
+                    .or_else(|| dir.join("tun_flags").exists().then(|| "tun".to_string()))?,
+            };
+            let flags = read_attr(&dir, "flags")
+                .and_then(|f| u32::from_str_radix(f.trim_start_matches("0x"), 16).ok())
+                .unwrap_or_default();
+            (flags & IFF_UP != 0).then(|| VpnInterface {
+                mtu: read_attr(&dir, "mtu").and_then(|m| m.parse().ok()),
+                name,
+                kind,
+            })
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/network")
+            .join(name)
+    }
+
+    #[test]
+    fn test_resolved_stub_with_vpn() {
+        let net = collect(&fixture("resolved")).unwrap().unwrap();
+        let gateways: Vec<(&str, Option<&str>, u32)> = net
+            .default_gateways
+            .iter()
+            .map(|g| (g.interface.as_str(), g.address.as_deref(), g.metric))
+            .collect();
+        assert_eq!(
+            gateways,
+            vec![
+                // 0.0.0.0/1 and 128.0.0.0/1 onto the WireGuard link, reported once
+                ("corp0", None, 0),
+                ("wlp2s0", Some("192.168.1.1"), 600),
+                ("wlp2s0", Some("fe80::1"), 600),
+                ("enx00e04c680001", Some("10.1.0.1"), 20100),
+            ]
+        );
+
+        assert!(net.systemd_resolved_stub);
+        assert_eq!(net.dns_servers, vec!["10.8.0.1", "192.168.1.1"]);
+        assert_eq!(net.search_domains, vec!["corp.example.com", "lan"]);
+
+        let vpns: Vec<(&str, &str)> = net
+            .vpn_interfaces
+            .iter()
+            .map(|v| (v.name.as_str(), v.kind.as_str()))
+            .collect();
+        // tun1 is down; enx... and wlp2s0 are not tunnels
+        assert_eq!(vpns, vec![("corp0", "wireguard"), ("tun0", "tun")]);
+        assert_eq!(net.vpn_interfaces[1].mtu, Some(1500));
+    }
+
+    #[test]
+    fn test_static_resolv_conf() {
+        let net = collect(&fixture("static")).unwrap().unwrap();
+        assert!(!net.systemd_resolved_stub);
+        assert_eq!(net.dns_servers, vec!["10.20.0.1", "2001:db8::53"]);
+        // The later domain line replaces search
+        assert_eq!(net.search_domains, vec!["branch.example.net"]);
+        assert!(net.default_gateways.is_empty());
+        assert!(net.vpn_interfaces.is_empty());
+    }
+
+    #[test]
+    fn test_parse_ipv4_routes() {
+        let table =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+            eth0\t00000000\t0100A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+            eth0\t0000A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n\
+            ppp0\t00000000\t00000000\t0001\t0\t0\t0\t00000000\t0\t0\t0\n\
+            eth1\t00000000\t0100A8C0\t0002\t0\t0\t0\t00000000\t0\t0\t0\n";
+        let gateways = parse_ipv4_routes(table);
+        // eth1's route is not up
+        assert_eq!(gateways.len(), 2);
+        assert_eq!(gateways[0].interface, "eth0");
+        assert_eq!(gateways[0].metric, 100);
+        if cfg!(target_endian = "little") {
+            assert_eq!(gateways[0].address.as_deref(), Some("192.168.0.1"));
+        }
+        assert_eq!(gateways[1].interface, "ppp0");
+        assert_eq!(gateways[1].address, None);
+    }
+
+    #[test]
+    fn test_parse_ipv6_routes() {
+        let table = "\
+            00000000000000000000000000000000 01 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000001 00000000 00000001 tun0\n\
+            80000000000000000000000000000000 01 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000001 00000000 00000001 tun0\n\
+            20010db8000000000000000000000000 20 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001 eth0\n\
+            00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200 lo\n";
+        let gateways = parse_ipv6_routes(table);
+        let summary: Vec<(&str, Option<&str>)> = gateways
+            .iter()
+            .map(|g| (g.interface.as_str(), g.address.as_deref()))
+            .collect();
+        // The unreachable default on lo is not up
+        assert_eq!(summary, vec![("tun0", None), ("tun0", None)]);
+    }
+
+    #[test]
+    fn test_no_sources() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
@@ -0,0 +1,40 @@
+//! `/etc/os-release` lookups shared by the SBOM and vulnerability matching.
+
+use std::path::Path;
+
+/// Read os-release from `etc/os-release`, falling back to `usr/lib/os-release`, under `root`.
+pub fn read(root: &Path) -> Option<String> {
+    ["etc/os-release", "usr/lib/os-release"]
+        .iter()
+        .find_map(|p| std::fs::read_to_string(root.join(p)).ok())
+}
+
+/// Look up a field in os-release syntax, stripping optional quotes.
+pub fn field(contents: &str, key: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (k, v) = line.trim().split_once('=')?;
+        if k != key {
+            return None;
+        }
+        let v = v.trim().trim_matches(|c| c == '"' || c == '\'');
+        (!v.is_empty()).then(|| v.to_string())
+    })
+}
+
+/// The distribution `ID` (e.g. `debian`, `fedora`).
+pub fn distro_id(root: &Path) -> Option<String> {
+    read(root).and_then(|contents| field(&contents, "ID"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field() {
+        let contents = "NAME=\"Ubuntu\"\nID=ubuntu\nID_LIKE=debian\nVERSION_ID=\"24.04\"\n";
+        assert_eq!(field(contents, "ID").as_deref(), Some("ubuntu"));
+        assert_eq!(field(contents, "VERSION_ID").as_deref(), Some("24.04"));
+        assert_eq!(field(contents, "BUILD_ID"), None);
+    }
+}
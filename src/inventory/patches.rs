@@ -0,0 +1,306 @@
+//! OS patch level: last successful package upgrade from package manager history, pending
+//! updates where the OS caches a count, and pending reboot state.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use flate2::read::GzDecoder;
+
+use crate::models::PatchStatus;
+
+const APT_HISTORY: &str = "var/log/apt/history.log";
+const DNF_RPM_LOG: &str = "var/log/dnf.rpm.log";
+/// Written by Ubuntu's update-notifier after each apt update.
+const UPDATES_AVAILABLE: &str = "var/lib/update-notifier/updates-available";
+const REBOOT_REQUIRED: &[&str] = &["run/reboot-required", "var/run/reboot-required"];
+
+/// Read patch state. Returns `None` on hosts with neither dpkg nor rpm.
+pub fn collect(root: &Path, now: DateTime<Utc>) -> Result<Option<PatchStatus>> {
+    let (package_manager, last_update) = if root.join("var/lib/dpkg/status").is_file() {
+        let last = search_rotated(&root.join(APT_HISTORY), last_apt_upgrade)?
+            .and_then(|t| Local.from_local_datetime(&t).earliest())
+            .map(|t| t.with_timezone(&Utc));
+        ("apt", last)
+    } else if root.join("var/lib/rpm").is_dir() {
+        let last = search_rotated(&root.join(DNF_RPM_LOG), last_dnf_upgrade)?;
+        ("dnf", last)
+    } else {
+        return Ok(None);
+    };
+
+    let pending = read_optional(&root.join(UPDATES_AVAILABLE))?
+        .map(|text| parse_updates_available(&text))
+        .unwrap_or_default();
+
+    let reboot_flag = REBOOT_REQUIRED
+        .iter()
+        .map(|p| root.join(p))
+        .find(|p| p.is_file());
+    let reboot_required_packages = match &reboot_flag {
+        Some(flag) => {
+            let list = flag.with_extension("pkgs");
+            let mut packages: Vec<String> = read_optional(&list)?
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_string)
+                .collect();
+            packages.sort();
+            packages.dedup();
+            packages
+        }
+        None => Vec::new(),
+    };
+
+    Ok(Some(PatchStatus {
+        package_manager: package_manager.to_string(),
+        last_update: last_update.map(|t| t.to_rfc3339()),
+        days_since_update: last_update.map(|t| (now - t).num_days()),
+        pending_updates: pending.0,
+        pending_security_updates: pending.1,
+        reboot_required: reboot_flag.is_some(),
+        reboot_required_packages,
+    }))
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Search a log and then its rotated generations (`.1`, `.2.gz`, ...), newest first, returning
+/// the first value `find` yields. A missing current log is skipped, as logrotate leaves none
+/// until the package manager next writes one.
+fn search_rotated<T>(path: &Path, find: impl Fn(&str) -> Option<T>) -> Result<Option<T>> {
+    if let Some(found) = read_optional(path)?.and_then(|text| find(&text)) {
+        return Ok(Some(found));
+    }
+    for generation in 1.. {
+        let text = match read_optional(&rotated(path, generation, ""))? {
+            Some(text) => text,
+            None => match read_gzip(&rotated(path, generation, ".gz"))? {
+                Some(text) => text,
+                None => break,
+            },
+        };
+        if let Some(found) = find(&text) {
+            return Ok(Some(found));
+        }
+    }
+    Ok(None)
+}
+
+fn rotated(path: &Path, generation: u32, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}{}", generation, suffix));
+    PathBuf::from(name)
+}
+
+fn read_gzip(path: &Path) -> Result<Option<String>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("failed to open {}", path.display())),
+    };
+    let mut text = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut text)
+        .with_context(|| format!("failed to decompress {}", path.display()))?;
+    Ok(Some(text))
+}
+
+/// End time of the latest apt transaction that upgraded packages without errors. apt logs
+/// local time.
+///
+/// Entries are blank-line separated blocks of `Key: value` lines from `Start-Date` to
+/// `End-Date`.
+pub fn last_apt_upgrade(history: &str) -> Option<NaiveDateTime> {
+    history
+        .split("\n\n")
+        .filter_map(|entry| {
+            let mut end = None;
+            let mut upgraded = false;
+            let mut failed = false;
+            for line in entry.lines() {
+                let Some((key, value)) = line.split_once(": ") else {
+                    continue;
+                };
+                match key {
+                    "End-Date" => {
+                        end = NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d  %H:%M:%S").ok()
+                    }
+                    "Upgrade" => upgraded = true,
+                    "Error" => failed = true,
+                    _ => {}
+                }
+            }
+            // An entry without End-Date was interrupted
+            (upgraded && !failed).then_some(end)?
+        })
+        .max()
+}
+
+/// Time of the latest package upgrade in `dnf.rpm.log`, whose lines look like
+/// `2025-10-01T06:25:13+0000 SUBDEBUG Upgrade: openssl-1:3.0.7-27.el9.x86_64`.
+pub fn last_dnf_upgrade(log: &str) -> Option<DateTime<Utc>> {
+    log.lines()
+        .filter_map(|line| {
+            let (time, rest) = line.split_once(' ')?;
+            let (_, action) = rest.split_once(' ')?;
+            if !action.starts_with("Upgrade: ") {
+                return None;
+            }
+            DateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%z").ok()
+        })
+        .max()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Pending and security update counts from update-notifier's summary, e.g.
+/// "12 updates can be applied immediately." and "5 of these updates are standard security
+/// updates.". Older releases say "12 packages can be updated." and "5 updates are security
+/// updates.".
+pub fn parse_updates_available(text: &str) -> (Option<u32>, Option<u32>) {
+    let leading_number = |line: &str| line.split_whitespace().next()?.parse::<u32>().ok();
+    let mut pending = None;
+    let mut security = None;
+    for line in text.lines().map(str::trim) {
+        if line.contains("can be applied immediately") || line.contains("can be updated") {
+            pending = pending.or_else(|| leading_number(line));
+        } else if line.contains("security update") && !line.contains("ESM") {
+            security = security.or_else(|| leading_number(line));
+        }
+    }
+    // A pending count without a security line means none are security updates
+    (pending, security.or(pending.map(|_| 0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::path::PathBuf;
+
+    fn fixture_root(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/patches")
+            .join(name)
+    }
+
+    fn naive(day: u32, h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 10, day)
+            .unwrap()
+            .and_hms_opt(h, m, s)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_last_apt_upgrade() {
+        let history = std::fs::read_to_string(fixture_root("ubuntu").join(APT_HISTORY)).unwrap();
+        // The later install-only and failed upgrade entries do not count
+        assert_eq!(last_apt_upgrade(&history), Some(naive(6, 6, 41, 52)));
+        assert_eq!(last_apt_upgrade(""), None);
+    }
+
+    #[test]
+    fn test_last_dnf_upgrade() {
+        let log = std::fs::read_to_string(fixture_root("rhel").join(DNF_RPM_LOG)).unwrap();
+        assert_eq!(
+            last_dnf_upgrade(&log).unwrap().to_rfc3339(),
+            "2025-10-02T04:12:09+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_updates_available() {
+        let current = "\n12 updates can be applied immediately.\n5 of these updates are standard security updates.\nTo see these additional updates run: apt list --upgradable\n\n1 additional security update can be applied with ESM Apps.\n";
+        assert_eq!(parse_updates_available(current), (Some(12), Some(5)));
+        let old = "3 packages can be updated.\n0 updates are security updates.\n";
+        assert_eq!(parse_updates_available(old), (Some(3), Some(0)));
+        let no_security = "2 updates can be applied immediately.\n";
+        assert_eq!(parse_updates_available(no_security), (Some(2), Some(0)));
+        assert_eq!(parse_updates_available(""), (None, None));
+    }
+
+    #[test]
+    fn test_collect_ubuntu() {
+        let now = Utc.with_ymd_and_hms(2025, 10, 16, 12, 0, 0).unwrap();
+        let status = collect(&fixture_root("ubuntu"), now).unwrap().unwrap();
+        assert_eq!(status.package_manager, "apt");
+        let expected = Local
+            .from_local_datetime(&naive(6, 6, 41, 52))
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(status.last_update, Some(expected.to_rfc3339()));
+        assert_eq!(status.days_since_update, Some((now - expected).num_days()));
+        assert_eq!(status.pending_updates, Some(12));
+        assert_eq!(status.pending_security_updates, Some(5));
+        assert!(status.reboot_required);
+        assert_eq!(
+            status.reboot_required_packages,
+            vec!["linux-base", "linux-image-6.8.0-85-generic"]
+        );
+    }
+
+    #[test]
+    fn test_collect_rhel() {
+        let now = Utc.with_ymd_and_hms(2025, 10, 16, 12, 0, 0).unwrap();
+        let status = collect(&fixture_root("rhel"), now).unwrap().unwrap();
+        assert_eq!(status.package_manager, "dnf");
+        assert_eq!(status.days_since_update, Some(14));
+        assert_eq!(status.pending_updates, None);
+        assert!(!status.reboot_required);
+        assert!(status.reboot_required_packages.is_empty());
+    }
+
+    #[test]
+    fn test_collect_rotated_apt_history() {
+        let now = Utc.with_ymd_and_hms(2025, 10, 16, 12, 0, 0).unwrap();
+        let status = collect(&fixture_root("rotated"), now).unwrap().unwrap();
+        // history.log only has an install; the newest upgrade is in history.log.1.gz
+        let expected = Local
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2025, 9, 12)
+                    .unwrap()
+                    .and_hms_opt(6, 30, 5)
+                    .unwrap(),
+            )
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(status.last_update, Some(expected.to_rfc3339()));
+        assert!(!status.reboot_required);
+    }
+
+    #[test]
+    fn test_collect_rotated_dnf_log() {
+        let now = Utc.with_ymd_and_hms(2025, 10, 16, 12, 0, 0).unwrap();
+        let status = collect(&fixture_root("rhel_rotated"), now)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            status.last_update.as_deref(),
+            Some("2025-09-18T04:05:31+00:00")
+        );
+    }
+
+    #[test]
+    fn test_search_rotated_stops_at_gap() {
+        let path = fixture_root("rotated").join("var/log/apt/missing.log");
+        assert_eq!(search_rotated(&path, |_| Some(())).unwrap(), None);
+    }
+
+    #[test]
+    fn test_no_package_manager() {
+        assert!(collect(Path::new("/nonexistent"), Utc::now())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,330 @@
+//! PCI devices from `/sys/bus/pci/devices`, named from a `pci.ids` database.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::read_attr;
+use crate::config::{self, PciConfig};
+use crate::models::PciDevice;
+
+const PCI_DEVICES: &str = "sys/bus/pci/devices";
+
+/// Where distributions install `pci.ids`, relative to the root.
+const SYSTEM_PCI_IDS: &[&str] = &[
+    "usr/share/hwdata/pci.ids",
+    "usr/share/misc/pci.ids",
+    "usr/share/pci.ids",
+];
+
+/// Names parsed from a `pci.ids` file.
+#[derive(Debug, Default)]
+pub struct PciIds {
+    vendors: HashMap<u16, IdsVendor>,
+    classes: HashMap<u8, IdsClass>,
+}
+
+#[derive(Debug, Default)]
+struct IdsVendor {
+    name: String,
+    devices: HashMap<u16, IdsDevice>,
+}
+
+#[derive(Debug, Default)]
+struct IdsDevice {
+    name: String,
+    subsystems: HashMap<(u16, u16), String>,
+}
+
+#[derive(Debug, Default)]
+struct IdsClass {
+    name: String,
+    subclasses: HashMap<u8, String>,
+}
+
+impl PciIds {
+    /// Parse the `pci.ids` text format. Malformed lines are ignored.
+    pub fn parse(contents: &str) -> Self {
+        let mut ids = Self::default();
+        let mut vendor: Option<u16> = None;
+        let mut device: Option<u16> = None;
+        let mut class: Option<u8> = None;
+
+        for line in contents.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let depth = line.chars().take_while(|c| *c == '\t').count();
+            let body = line.trim_start_matches('\t');
+
+            match depth {
+                0 => {
+                    device = None;
+                    if let Some(rest) = body.strip_prefix("C ") {
+                        vendor = None;
+                        class = split_id(rest).and_then(|(id, name)| {
+                            let id = u8::from_str_radix(id, 16).ok()?;
+                            ids.classes.entry(id).or_default().name = name.to_string();
+                            Some(id)
+                        });
+                    } else {
+                        class = None;
+                        vendor = split_id(body).and_then(|(id, name)| {
+                            // Other top-level lists (e.g. "X 00 ...") have non-hex IDs
+                            let id = u16::from_str_radix(id, 16).ok()?;
+                            ids.vendors.entry(id).or_default().name = name.to_string();
+                            Some(id)
+                        });
+                    }
+                }
+                1 => {
+                    let Some((id, name)) = split_id(body) else {
+                        continue;
+                    };
+                    if let Some(v) = vendor {
+                        device = u16::from_str_radix(id, 16).ok();
+                        if let Some(d) = device {
+                            let vendor = ids.vendors.entry(v).or_default();
+                            vendor.devices.entry(d).or_default().name = name.to_string();
+                        }
+                    } else if let (Some(c), Ok(sub)) = (class, u8::from_str_radix(id, 16)) {
+                        let class = ids.classes.entry(c).or_default();
+                        class.subclasses.insert(sub, name.to_string());
+                    }
+                }
+                2 => {
+                    let (Some(v), Some(d)) = (vendor, device) else {
+                        continue;
+                    };
+                    // "\t\t1028 0abc  Subsystem name"
+                    let mut parts = body.splitn(3, ' ');
+                    let (Some(sv), Some(sd), Some(name)) =
+                        (parts.next(), parts.next(), parts.next())
+                    else {
+                        continue;
+                    };
+                    if let (Ok(sv), Ok(sd)) =
+                        (u16::from_str_radix(sv, 16), u16::from_str_radix(sd, 16))
+                    {
+                        let device = ids
+                            .vendors
+                            .entry(v)
+                            .or_default()
+                            .devices
+                            .entry(d)
+                            .or_default();
+                        device.subsystems.insert((sv, sd), name.trim().to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        ids
+    }
+
+    pub fn vendor(&self, vendor: u16) -> Option<&str> {
+        self.vendors.get(&vendor).map(|v| v.name.as_str())
+    }
+
+    pub fn device(&self, vendor: u16, device: u16) -> Option<&str> {
+        self.vendors
+            .get(&vendor)?
+            .devices
+            .get(&device)
+            .map(|d| d.name.as_str())
+    }
+
+    pub fn subsystem(&self, vendor: u16, device: u16, sub: (u16, u16)) -> Option<&str> {
+        self.vendors
+            .get(&vendor)?
+            .devices
+            .get(&device)?
+            .subsystems
+            .get(&sub)
+            .map(String::as_str)
+    }
+
+    /// The most specific name for a 24-bit class code: subclass if known, else class.
+    pub fn class(&self, class_code: u32) -> Option<&str> {
+        let class = self.classes.get(&((class_code >> 16) as u8))?;
+        class
+            .subclasses
+            .get(&((class_code >> 8) as u8))
+            .map(String::as_str)
+            .or(Some(class.name.as_str()))
+    }
+}
+
+/// Split "8086  Intel Corporation" into ID and name.
+fn split_id(s: &str) -> Option<(&str, &str)> {
+    let (id, name) = s.split_once(' ')?;
+    Some((id, name.trim()))
+}
+
+/// Candidate `pci.ids` locations, most specific first: configured path, a copy shipped next to
+/// the executable, then the system copies under `root`.
+fn pci_ids_candidates(root: &Path, cfg: &PciConfig) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    paths.extend(cfg.pci_ids.clone());
+    if let Ok(dir) = config::exe_dir() {
+        paths.push(dir.join("pci.ids"));
+    }
+    paths.extend(SYSTEM_PCI_IDS.iter().map(|p| root.join(p)));
+    paths
+}
+
+/// Enumerate PCI devices, resolving names from the first `pci.ids` found.
+pub fn collect(root: &Path, cfg: &PciConfig) -> Result<Vec<PciDevice>> {
+    let dir = root.join(PCI_DEVICES);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let ids = pci_ids_candidates(root, cfg)
+        .iter()
+        .find_map(|p| std::fs::read_to_string(p).ok())
+        .map(|contents| PciIds::parse(&contents))
+        .unwrap_or_default();
+
+    let mut devices = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to list {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| read_device(&e.path(), &ids))
+        .collect::<Vec<_>>();
+    devices.sort_by(|a, b| a.address.cmp(&b.address));
+    Ok(devices)
+}
+
+fn read_device(path: &Path, ids: &PciIds) -> Option<PciDevice> {
+    let hex = |name: &str| -> Option<u32> {
+        let value = read_attr(path, name)?;
+        u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+    };
+
+    let vendor = hex("vendor")? as u16;
+    let device = hex("device")? as u16;
+    let class_code = hex("class").unwrap_or(0);
+    let subsystem = match (hex("subsystem_vendor"), hex("subsystem_device")) {
+        (Some(sv), Some(sd)) => Some((sv as u16, sd as u16)),
+        _ => None,
+    };
+
+    // uevent carries the slot name and bound driver without resolving any symlinks
+    let uevent = read_attr(path, "uevent").unwrap_or_default();
+    let uevent_field = |key: &str| {
+        uevent
+            .lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix('=').map(str::to_string))
+    };
+    let address = uevent_field("PCI_SLOT_NAME")
+        .or_else(|| Some(path.file_name()?.to_string_lossy().into_owned()))?;
+    let driver = uevent_field("DRIVER").or_else(|| {
+        std::fs::read_link(path.join("driver"))
+            .ok()?
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+    });
+
+    Some(PciDevice {
+        address,
+        vendor_id: format!("{:04x}", vendor),
+        device_id: format!("{:04x}", device),
+        subsystem_vendor_id: subsystem.map(|s| format!("{:04x}", s.0)),
+        subsystem_device_id: subsystem.map(|s| format!("{:04x}", s.1)),
+        class_code: format!("{:06x}", class_code),
+        revision: hex("revision").map(|r| format!("{:02x}", r)),
+        driver,
+        vendor_name: ids.vendor(vendor).map(str::to_string),
+        device_name: ids.device(vendor, device).map(str::to_string),
+        subsystem_name: subsystem
+            .and_then(|s| ids.subsystem(vendor, device, s))
+            .map(str::to_string),
+        class_name: ids.class(class_code).map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pci")
+    }
+
+    #[test]
+    fn test_parse_pci_ids() {
+        let contents =
+            std::fs::read_to_string(fixture_root().join("usr/share/hwdata/pci.ids")).unwrap();
+        let ids = PciIds::parse(&contents);
+        assert_eq!(ids.vendor(0x8086), Some("Intel Corporation"));
+        assert_eq!(
+            ids.device(0x8086, 0x06f0),
+            Some("Comet Lake PCH-LP CNVi WiFi")
+        );
+        assert_eq!(
+            ids.subsystem(0x8086, 0x06f0, (0x8086, 0x0074)),
+            Some("Wi-Fi 6 AX201 160MHz")
+        );
+        assert_eq!(ids.class(0x030000), Some("VGA compatible controller"));
+        assert_eq!(ids.class(0x0280ff), Some("Network controller"));
+        assert_eq!(ids.vendor(0xffff), None);
+    }
+
+    // Fixture device directories avoid ':' so the tree checks out on Windows; the addresses
+    // come from PCI_SLOT_NAME in uevent
+    #[test]
+    fn test_collect_devices() {
+        let devices = collect(&fixture_root(), &PciConfig::default()).unwrap();
+        assert_eq!(devices.len(), 3);
+
+        let gpu = &devices[0];
+        assert_eq!(gpu.address, "0000:00:02.0");
+        assert_eq!(gpu.vendor_id, "8086");
+        assert_eq!(gpu.class_code, "030000");
+        assert_eq!(gpu.driver.as_deref(), Some("i915"));
+        assert_eq!(gpu.class_name.as_deref(), Some("VGA compatible controller"));
+
+        let wifi = &devices[1];
+        assert_eq!(
+            wifi.device_name.as_deref(),
+            Some("Comet Lake PCH-LP CNVi WiFi")
+        );
+        assert_eq!(wifi.subsystem_name.as_deref(), Some("Wi-Fi 6 AX201 160MHz"));
+        assert_eq!(wifi.driver.as_deref(), Some("iwlwifi"));
+        assert_eq!(wifi.revision.as_deref(), Some("00"));
+
+        // Discrete GPU with no driver bound and no subsystem entry in pci.ids
+        let dgpu = &devices[2];
+        assert_eq!(dgpu.vendor_name.as_deref(), Some("NVIDIA Corporation"));
+        assert_eq!(
+            dgpu.device_name.as_deref(),
+            Some("TU117M [GeForce GTX 1650 Mobile / Max-Q]")
+        );
+        assert_eq!(dgpu.class_name.as_deref(), Some("3D controller"));
+        assert_eq!(dgpu.driver, None);
+        assert_eq!(dgpu.subsystem_name, None);
+    }
+
+    #[test]
+    fn test_configured_pci_ids_takes_precedence() {
+        let dir = std::env::temp_dir().join("inventory-agent-pci-ids-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let custom = dir.join("pci.ids");
+        std::fs::write(&custom, "8086  Custom Intel Name\n").unwrap();
+
+        let cfg = PciConfig {
+            pci_ids: Some(custom),
+        };
+        let devices = collect(&fixture_root(), &cfg).unwrap();
+        assert_eq!(devices[0].vendor_name.as_deref(), Some("Custom Intel Name"));
+        assert_eq!(devices[0].device_name, None);
+    }
+
+    #[test]
+    fn test_missing_sysfs() {
+        let devices = collect(Path::new("/nonexistent"), &PciConfig::default()).unwrap();
+        assert!(devices.is_empty());
+    }
+}
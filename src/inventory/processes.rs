@@ -0,0 +1,290 @@
+//! Running process snapshot from `/proc`, with processes matching the configured deny-list
+//! flagged unless the allow-list exempts them.
+//!
+//! The snapshot is capped at `max_processes`; flagged processes are kept first, then the
+//! largest by resident memory.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use super::glob_match;
+use crate::config::ProcessConfig;
+use crate::models::{ProcessInfo, ProcessSnapshot};
+
+/// USER_HZ, the unit of the times in `/proc/<pid>/stat`; 100 on every mainstream architecture.
+const CLOCK_TICKS: u64 = 100;
+
+/// PID of kthreadd, the parent of all kernel threads.
+const KTHREADD: u32 = 2;
+
+/// Take a snapshot. Returns `None` when disabled or where there is no `/proc`.
+pub fn collect(
+    root: &Path,
+    cfg: &ProcessConfig,
+    now: DateTime<Utc>,
+) -> Result<Option<ProcessSnapshot>> {
+    let proc = root.join("proc");
+    if !cfg.enabled || !proc.is_dir() {
+        return Ok(None);
+    }
+    let boot_time = std::fs::read_to_string(proc.join("stat"))
+        .ok()
+        .and_then(|stat| parse_boot_time(&stat));
+    let users = std::fs::read_to_string(root.join("etc/passwd"))
+        .map(|passwd| parse_users(&passwd))
+        .unwrap_or_default();
+
+    let mut processes = Vec::new();
+    let entries =
+        std::fs::read_dir(&proc).with_context(|| format!("failed to read {}", proc.display()))?;
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // The process may exit while we read it
+        let Some(mut process) = read_process(&entry.path(), pid, boot_time, now) else {
+            continue;
+        };
+        if pid == KTHREADD || process.parent_pid == Some(KTHREADD) {
+            continue;
+        }
+        process.user = process.uid.and_then(|uid| users.get(&uid).cloned());
+        let executable = process.executable.as_deref();
+        // The allow-list carves exceptions out of broad deny patterns such as "/tmp/*"
+        process.flagged = matches_executable(&cfg.deny_executables, &process.name, executable)
+            && !matches_executable(&cfg.allow_executables, &process.name, executable);
+        processes.push(process);
+    }
+
+    let total = processes.len();
+    processes.sort_by(|a, b| {
+        b.flagged
+            .cmp(&a.flagged)
+            .then(b.memory_bytes.cmp(&a.memory_bytes))
+            .then(a.pid.cmp(&b.pid))
+    });
+    processes.truncate(cfg.max_processes);
+
+    Ok(Some(ProcessSnapshot {
+        total_processes: total,
+        truncated: total > processes.len(),
+        processes,
+    }))
+}
+
+fn read_process(
+    dir: &Path,
+    pid: u32,
+    boot_time: Option<i64>,
+    now: DateTime<Utc>,
+) -> Option<ProcessInfo> {
+    let status = std::fs::read_to_string(dir.join("status")).ok()?;
+    let stat = std::fs::read_to_string(dir.join("stat")).ok()?;
+    let field = |key: &str| {
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+    let times = parse_stat_times(&stat);
+
+    let start = times.zip(boot_time).and_then(|((_, start_ticks), boot)| {
+        DateTime::from_timestamp(boot + (start_ticks / CLOCK_TICKS) as i64, 0)
+    });
+    // Average over the process lifetime; there is no second sample to diff against
+    let cpu_percent = times.zip(start).and_then(|((cpu_ticks, _), start)| {
+        let elapsed = (now - start).num_seconds();
+        (elapsed > 0).then(|| {
+            let percent = cpu_ticks as f64 / CLOCK_TICKS as f64 / elapsed as f64 * 100.0;
+            (percent * 10.0).round() / 10.0
+        })
+    });
+
+    Some(ProcessInfo {
+        pid,
+        parent_pid: field("PPid").and_then(|v| v.parse().ok()),
+        name: field("Name").unwrap_or_default().to_string(),
+        uid: field("Uid")
+            .and_then(|v| v.split_whitespace().next())
+            .and_then(|v| v.parse().ok()),
+        user: None,
+        // Unreadable for other users' processes unless running as root
+        executable: std::fs::read_link(dir.join("exe")).ok().map(|p| {
+            p.to_string_lossy()
+                .trim_end_matches(" (deleted)")
+                .to_string()
+        }),
+        start_time: start.map(|t| t.to_rfc3339()),
+        cpu_percent,
+        memory_bytes: field("VmRSS")
+            .and_then(|v| v.trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kb| kb * 1024),
+        flagged: false,
+    })
+}
+
+/// `btime` (boot time in Unix seconds) from `/proc/stat`.
+fn parse_boot_time(stat: &str) -> Option<i64> {
+    stat.lines()
+        .find_map(|l| l.strip_prefix("btime "))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// CPU ticks used (utime + stime) and start time in ticks since boot from `/proc/<pid>/stat`.
+///
+/// The name in field 2 may contain spaces and parentheses, so fields are counted from the last
+/// `)`.
+pub fn parse_stat_times(stat: &str) -> Option<(u64, u64)> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    // rest starts at field 3 (state)
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3)?.parse::<u64>().ok();
+    Some((field(14)? + field(15)?, field(22)?))
+}
+
+fn parse_users(passwd: &str) -> HashMap<u32, String> {
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+/// Patterns with a `/` match the executable path, others the process name or executable file
+/// name. `*` matches any run of characters.
+pub fn matches_executable(patterns: &[String], name: &str, executable: Option<&str>) -> bool {
+    let file_name = executable.and_then(|e| e.rsplit('/').next());
+    patterns.iter().any(|pattern| {
+        if pattern.contains('/') {
+            executable.is_some_and(|e| glob_match(pattern, e))
+        } else {
+            glob_match(pattern, name) || file_name.is_some_and(|f| glob_match(pattern, f))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/processes")
+    }
+
+    fn now() -> DateTime<Utc> {
+        // 1000 seconds after the fixture's btime
+        Utc.timestamp_opt(1_760_000_000 + 1000, 0).unwrap()
+    }
+
+    fn config(deny: &[&str], allow: &[&str], max_processes: usize) -> ProcessConfig {
+        ProcessConfig {
+            enabled: true,
+            max_processes,
+            deny_executables: deny.iter().map(|d| d.to_string()).collect(),
+            allow_executables: allow.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_stat_times() {
+        let stat = "4242 (tmux: server) S 1 4242 4242 0 -1 4194560 512 0 0 0 250 50 0 0 20 0 1 0 20000 9990144 1024 18446744073709551615 1 1 0 0 0 0 0 4096 2 0 0 0 17 3 0 0 0 0 0";
+        assert_eq!(parse_stat_times(stat), Some((300, 20000)));
+        assert_eq!(parse_stat_times("garbage"), None);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let snapshot = collect(&fixture_root(), &config(&["xmrig", "nc*"], &[], 10), now())
+            .unwrap()
+            .unwrap();
+        // kthreadd and its kworker child are skipped
+        assert_eq!(snapshot.total_processes, 4);
+        assert!(!snapshot.truncated);
+
+        let pids: Vec<u32> = snapshot.processes.iter().map(|p| p.pid).collect();
+        // Flagged first, then by memory
+        assert_eq!(pids, vec![5150, 812, 1, 4242]);
+
+        let miner = &snapshot.processes[0];
+        assert!(miner.flagged);
+        assert_eq!(miner.user.as_deref(), Some("alice"));
+
+        let tmux = snapshot.processes.iter().find(|p| p.pid == 4242).unwrap();
+        assert_eq!(tmux.name, "tmux: server");
+        assert_eq!(tmux.parent_pid, Some(1));
+        assert_eq!(tmux.memory_bytes, Some(4096 * 1024));
+        // Started 200 s after boot, used 3 s of CPU in the 800 s since
+        assert_eq!(
+            tmux.start_time.as_deref(),
+            Some("2025-10-09T08:56:40+00:00")
+        );
+        assert_eq!(tmux.cpu_percent, Some(0.4));
+        assert!(!tmux.flagged);
+    }
+
+    #[test]
+    fn test_snapshot_cap_keeps_flagged() {
+        let snapshot = collect(&fixture_root(), &config(&["xmrig"], &[], 2), now())
+            .unwrap()
+            .unwrap();
+        assert!(snapshot.truncated);
+        assert_eq!(snapshot.total_processes, 4);
+        let pids: Vec<u32> = snapshot.processes.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![5150, 812]);
+    }
+
+    #[test]
+    fn test_allow_list_wins() {
+        let cfg = config(&["xmrig", "s*"], &["sshd"], 10);
+        let snapshot = collect(&fixture_root(), &cfg, now()).unwrap().unwrap();
+        let flagged: Vec<&str> = snapshot
+            .processes
+            .iter()
+            .filter(|p| p.flagged)
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(flagged, vec!["systemd", "xmrig"]);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let cfg = ProcessConfig::default();
+        assert!(!cfg.enabled);
+        assert!(collect(&fixture_root(), &cfg, now()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_matches_executable() {
+        let patterns: Vec<String> = ["/tmp/*", "nc*", "*miner*"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert!(matches_executable(
+            &patterns,
+            "payload",
+            Some("/tmp/payload")
+        ));
+        assert!(matches_executable(&patterns, "ncat", Some("/usr/bin/ncat")));
+        assert!(matches_executable(
+            &patterns,
+            "x",
+            Some("/opt/cpuminer-opt/cpuminer")
+        ));
+        assert!(matches_executable(&patterns, "cpuminer", None));
+        assert!(!matches_executable(
+            &patterns,
+            "sshd",
+            Some("/usr/sbin/sshd")
+        ));
+        assert!(!matches_executable(&patterns, "payload", None));
+    }
+}
@@ -0,0 +1,194 @@
+//! AV/EDR presence from a configurable product catalog, matched against installed packages,
+//! systemd units, running processes and well-known paths.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::read_attr;
+use super::systemd;
+use crate::config::SecurityProductRule;
+use crate::models::{SecurityProduct, SoftwarePackage};
+
+/// Kernel limit on process names in `/proc/<pid>/comm`, excluding the terminator.
+const COMM_LEN: usize = 15;
+
+/// Evaluate every catalog entry; products that are not found are reported as not installed.
+pub fn collect(
+    root: &Path,
+    catalog: &[SecurityProductRule],
+    packages: &[SoftwarePackage],
+) -> Result<Vec<SecurityProduct>> {
+    let processes = process_names(root);
+    Ok(catalog
+        .iter()
+        .map(|rule| evaluate(root, rule, packages, &processes))
+        .collect())
+}
+
+fn evaluate(
+    root: &Path,
+    rule: &SecurityProductRule,
+    packages: &[SoftwarePackage],
+    processes: &HashSet<String>,
+) -> SecurityProduct {
+    let mut evidence = Vec::new();
+    let mut installed = false;
+    let mut running = false;
+
+    let package = rule
+        .packages
+        .iter()
+        .find_map(|name| packages.iter().find(|p| &p.name == name));
+    if let Some(package) = package {
+        installed = true;
+        evidence.push(format!("package:{}", package.name));
+    }
+    for service in &rule.services {
+        // A masked unit is left behind by uninstallers and cannot run
+        if systemd::find_unit(root, service).is_some_and(|unit| !systemd::is_masked(&unit)) {
+            installed = true;
+            evidence.push(format!("service:{}", service));
+        }
+        if systemd::service_active(root, service) {
+            running = true;
+            evidence.push(format!("service-active:{}", service));
+        }
+    }
+    for path in &rule.paths {
+        if root.join(path.strip_prefix("/").unwrap_or(path)).exists() {
+            installed = true;
+            evidence.push(format!("path:{}", path.display()));
+        }
+    }
+    for process in &rule.processes {
+        if processes.contains(comm_name(process)) {
+            running = true;
+            evidence.push(format!("process:{}", process));
+        }
+    }
+
+    SecurityProduct {
+        name: rule.name.clone(),
+        category: rule.category.clone(),
+        // A running agent is installed even if none of its install markers matched
+        installed: installed || running,
+        running,
+        version: package.map(|p| p.version.clone()),
+        evidence,
+    }
+}
+
+/// Names of all running processes from `/proc/<pid>/comm`.
+fn process_names(root: &Path) -> HashSet<String> {
+    let Ok(entries) = std::fs::read_dir(root.join("proc")) else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|e| read_attr(&e.path(), "comm"))
+        .collect()
+}
+
+/// The kernel truncates process names; compare against the truncated form.
+fn comm_name(name: &str) -> &str {
+    match name.char_indices().nth(COMM_LEN) {
+        Some((i, _)) => &name[..i],
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::PackageSource;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/security_products")
+    }
+
+    fn package(name: &str, version: &str) -> SoftwarePackage {
+        SoftwarePackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: Some("amd64".to_string()),
+            vendor: None,
+            source: PackageSource::Dpkg,
+            source_package: None,
+            install_date: None,
+        }
+    }
+
+    fn product<'a>(products: &'a [SecurityProduct], name: &str) -> &'a SecurityProduct {
+        products.iter().find(|p| p.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_default_catalog() {
+        let packages = vec![
+            package("falcon-sensor", "7.18.0-17106"),
+            package("mdatp", "101.24082.0004"),
+        ];
+        let products = collect(
+            &fixture_root(),
+            &Config::default().security_products,
+            &packages,
+        )
+        .unwrap();
+
+        let falcon = product(&products, "CrowdStrike Falcon");
+        assert!(falcon.installed && falcon.running);
+        assert_eq!(falcon.version.as_deref(), Some("7.18.0-17106"));
+        assert_eq!(
+            falcon.evidence,
+            vec![
+                "package:falcon-sensor",
+                "service:falcon-sensor.service",
+                "service-active:falcon-sensor.service",
+                "path:/opt/CrowdStrike",
+                "process:falcon-sensor",
+            ]
+        );
+
+        // Installed but the daemon is stopped
+        let defender = product(&products, "Microsoft Defender for Endpoint");
+        assert!(defender.installed);
+        assert!(!defender.running);
+
+        // Only the process is seen; its name is truncated by the kernel
+        let sophos = product(&products, "Sophos Protection");
+        assert!(sophos.installed && sophos.running);
+        assert_eq!(sophos.version, None);
+
+        // clamav-daemon.service is masked in /etc
+        let clamav = product(&products, "ClamAV");
+        assert!(!clamav.installed && !clamav.running);
+        assert!(clamav.evidence.is_empty());
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        let rule = SecurityProductRule {
+            name: "Acme EDR".to_string(),
+            category: Some("edr".to_string()),
+            packages: vec![],
+            services: vec![],
+            processes: vec!["acmed".to_string()],
+            paths: vec![PathBuf::from("/opt/acme")],
+        };
+        let products = collect(&fixture_root(), &[rule], &[]).unwrap();
+        assert_eq!(products.len(), 1);
+        assert!(!products[0].installed);
+        assert_eq!(products[0].category.as_deref(), Some("edr"));
+    }
+
+    #[test]
+    fn test_comm_name() {
+        assert_eq!(comm_name("sophos_managementagent"), "sophos_manageme");
+        assert_eq!(comm_name("clamd"), "clamd");
+    }
+}
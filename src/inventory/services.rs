@@ -0,0 +1,427 @@
+//! systemd services with their enablement state, and scheduled jobs from cron tables and
+//! systemd timers.
+//!
+//! Enablement is worked out from the unit files and the `.wants`/`.requires` links under
+//! `/etc/systemd/system`, the way `systemctl is-enabled` does, without talking to systemd.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::systemd::{self, UNIT_DIRS};
+use crate::models::{JobSource, ScheduledJob, ServiceInventory, ServiceUnit, UnitEnablement};
+
+/// Local configuration directory, where enablement links and masks live.
+const SYSTEM_CONFIG: &str = "etc/systemd/system";
+
+/// `[Install]` keys that make a unit enableable.
+const INSTALL_KEYS: &[&str] = &["WantedBy", "RequiredBy", "UpheldBy", "Also", "Alias"];
+
+/// `[Timer]` keys that define when a timer elapses.
+const TIMER_KEYS: &[&str] = &[
+    "OnCalendar",
+    "OnActiveSec",
+    "OnBootSec",
+    "OnStartupSec",
+    "OnUnitActiveSec",
+    "OnUnitInactiveSec",
+];
+
+/// System crontabs have a user column.
+const SYSTEM_CRONTABS: &[&str] = &["etc/crontab"];
+const SYSTEM_CRONTAB_DIRS: &[&str] = &["etc/cron.d"];
+/// Per-user crontabs, named after their owner (Debian and Red Hat layouts).
+const USER_CRONTAB_DIRS: &[&str] = &["var/spool/cron/crontabs", "var/spool/cron"];
+/// Script directories run by run-parts, with the schedule they run on.
+const PERIODIC_DIRS: &[(&str, &str)] = &[
+    ("etc/cron.hourly", "@hourly"),
+    ("etc/cron.daily", "@daily"),
+    ("etc/cron.weekly", "@weekly"),
+    ("etc/cron.monthly", "@monthly"),
+];
+
+struct Unit {
+    name: String,
+    entries: Vec<(String, String, String)>,
+    enablement: UnitEnablement,
+}
+
+/// List services and scheduled jobs. Returns `None` when there is neither systemd nor cron.
+pub fn collect(root: &Path) -> Result<Option<ServiceInventory>> {
+    let has_systemd = UNIT_DIRS.iter().any(|d| root.join(d).is_dir());
+    let has_cron = SYSTEM_CRONTABS.iter().any(|f| root.join(f).is_file())
+        || SYSTEM_CRONTAB_DIRS
+            .iter()
+            .chain(USER_CRONTAB_DIRS)
+            .any(|d| root.join(d).is_dir());
+    if !has_systemd && !has_cron {
+        return Ok(None);
+    }
+
+    let units = read_units(root);
+    let services = units
+        .iter()
+        .filter(|u| u.name.ends_with(".service"))
+        .map(|u| {
+            let value = |section: &str, key: &str| {
+                systemd::unit_value(&u.entries, section, key).map(str::to_string)
+            };
+            ServiceUnit {
+                name: u.name.clone(),
+                description: value("Unit", "Description"),
+                enablement: u.enablement,
+                active: systemd::service_active(root, &u.name),
+                exec_start: value("Service", "ExecStart"),
+                user: value("Service", "User"),
+            }
+        })
+        .collect();
+
+    let mut scheduled_jobs: Vec<ScheduledJob> = units
+        .iter()
+        .filter(|u| u.name.ends_with(".timer"))
+        .map(|timer| timer_job(timer, &units))
+        .collect();
+    scheduled_jobs.extend(cron_jobs(root));
+
+    Ok(Some(ServiceInventory {
+        services,
+        scheduled_jobs,
+    }))
+}
+
+/// Load `.service` and `.timer` units, honoring directory precedence. Templates (`name@.service`)
+/// are skipped since only their instances run.
+fn read_units(root: &Path) -> Vec<Unit> {
+    let enabled = enabled_units(root);
+    let mut seen = HashSet::new();
+    let mut units = Vec::new();
+    for dir in UNIT_DIRS.iter().map(|d| root.join(d)) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut names: Vec<String> = entries
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|n| n.ends_with(".service") || n.ends_with(".timer"))
+            .filter(|n| !n.contains("@."))
+            .collect();
+        names.sort();
+        for name in names {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            let path = dir.join(&name);
+            let masked = systemd::is_masked(&path);
+            let entries = std::fs::read_to_string(&path)
+                .map(|text| systemd::parse_unit(&text))
+                .unwrap_or_default();
+            let enablement = if masked {
+                UnitEnablement::Masked
+            } else if enabled.contains(&name) {
+                UnitEnablement::Enabled
+            } else if entries
+                .iter()
+                .any(|(s, k, _)| s == "Install" && INSTALL_KEYS.contains(&k.as_str()))
+            {
+                UnitEnablement::Disabled
+            } else {
+                UnitEnablement::Static
+            };
+            units.push(Unit {
+                name,
+                entries,
+                enablement,
+            });
+        }
+    }
+    units.sort_by(|a, b| a.name.cmp(&b.name));
+    units
+}
+
+/// Units linked from a `.wants` or `.requires` directory by `systemctl enable`.
+fn enabled_units(root: &Path) -> HashSet<String> {
+    let Ok(entries) = std::fs::read_dir(root.join(SYSTEM_CONFIG)) else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.ends_with(".wants") || name.ends_with(".requires")
+        })
+        .filter_map(|e| std::fs::read_dir(e.path()).ok())
+        .flat_map(|links| links.flatten())
+        .map(|link| link.file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+fn timer_job(timer: &Unit, units: &[Unit]) -> ScheduledJob {
+    let schedule: Vec<String> = timer
+        .entries
+        .iter()
+        .filter(|(s, k, _)| s == "Timer" && TIMER_KEYS.contains(&k.as_str()))
+        .map(|(_, k, v)| format!("{}={}", k, v))
+        .collect();
+    // A timer activates the service of the same name unless Unit= says otherwise
+    let target = systemd::unit_value(&timer.entries, "Timer", "Unit")
+        .map(str::to_string)
+        .unwrap_or_else(|| timer.name.replace(".timer", ".service"));
+    let service = units.iter().find(|u| u.name == target);
+    let service_value =
+        |key: &str| service.and_then(|s| systemd::unit_value(&s.entries, "Service", key));
+
+    ScheduledJob {
+        source: JobSource::SystemdTimer,
+        name: timer.name.clone(),
+        schedule: schedule.join("; "),
+        user: Some(service_value("User").unwrap_or("root").to_string()),
+        command: service_value("ExecStart").map(str::to_string),
+        enabled: timer.enablement == UnitEnablement::Enabled,
+    }
+}
+
+fn cron_jobs(root: &Path) -> Vec<ScheduledJob> {
+    let mut jobs = Vec::new();
+    let mut read = |path: &Path, name: String, owner: Option<&str>| {
+        if let Ok(text) = std::fs::read_to_string(path) {
+            jobs.extend(parse_crontab(&text, &name, owner));
+        }
+    };
+
+    for file in SYSTEM_CRONTABS {
+        read(&root.join(file), format!("/{}", file), None);
+    }
+    for dir in SYSTEM_CRONTAB_DIRS {
+        for (path, file_name) in dir_files(&root.join(dir)) {
+            read(&path, format!("/{}/{}", dir, file_name), None);
+        }
+    }
+    for dir in USER_CRONTAB_DIRS {
+        for (path, user) in dir_files(&root.join(dir)) {
+            read(&path, format!("/{}/{}", dir, user), Some(&user));
+        }
+    }
+
+    for (dir, schedule) in PERIODIC_DIRS {
+        for (_, script) in dir_files(&root.join(dir)) {
+            let command = format!("/{}/{}", dir, script);
+            jobs.push(ScheduledJob {
+                source: JobSource::Cron,
+                name: command.clone(),
+                schedule: schedule.to_string(),
+                user: Some("root".to_string()),
+                command: Some(command),
+                enabled: true,
+            });
+        }
+    }
+    jobs
+}
+
+/// Regular files in a directory, sorted, skipping hidden files such as `.placeholder`.
+fn dir_files(dir: &Path) -> Vec<(std::path::PathBuf, String)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter(|e| e.path().is_file())
+        .map(|e| (e.path(), e.file_name().to_string_lossy().into_owned()))
+        .filter(|(_, name)| !name.starts_with('.'))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parse crontab entries. System crontabs (`owner` is `None`) have a user column after the
+/// schedule; per-user crontabs belong to `owner`.
+pub fn parse_crontab(text: &str, name: &str, owner: Option<&str>) -> Vec<ScheduledJob> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|line| {
+            let (schedule, rest) = if line.starts_with('@') {
+                line.split_once(char::is_whitespace)?
+            } else {
+                // Environment assignments such as `MAILTO=root` have no schedule
+                let first = line.split_whitespace().next()?;
+                if first.contains('=') {
+                    return None;
+                }
+                split_fields(line, 5)?
+            };
+            let (user, command) = match owner {
+                Some(owner) => (owner.to_string(), rest.trim()),
+                None => {
+                    let (user, command) = rest.trim().split_once(char::is_whitespace)?;
+                    (user.to_string(), command.trim())
+                }
+            };
+            Some(ScheduledJob {
+                source: JobSource::Cron,
+                name: name.to_string(),
+                schedule: schedule.split_whitespace().collect::<Vec<_>>().join(" "),
+                user: Some(user),
+                command: Some(command.to_string()),
+                enabled: true,
+            })
+        })
+        .collect()
+}
+
+/// Split off the first `n` whitespace-separated fields, returning them and the remainder.
+fn split_fields(line: &str, n: usize) -> Option<(&str, &str)> {
+    let mut end = 0;
+    for _ in 0..n {
+        let rest = &line[end..];
+        let start = end + (rest.len() - rest.trim_start().len());
+        let len = line[start..].find(char::is_whitespace)?;
+        end = start + len;
+    }
+    Some((&line[..end], &line[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/services")
+    }
+
+    fn service<'a>(inventory: &'a ServiceInventory, name: &str) -> &'a ServiceUnit {
+        inventory.services.iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_services() {
+        let inventory = collect(&fixture_root()).unwrap().unwrap();
+        let names: Vec<&str> = inventory.services.iter().map(|s| s.name.as_str()).collect();
+        // The getty@ template is skipped
+        assert_eq!(
+            names,
+            vec![
+                "backup.service",
+                "cups.service",
+                "dbus.service",
+                "fstrim.service",
+                "nginx.service",
+                "sshd.service",
+            ]
+        );
+
+        let sshd = service(&inventory, "sshd.service");
+        assert_eq!(sshd.enablement, UnitEnablement::Enabled);
+        assert!(sshd.active);
+        assert_eq!(sshd.description.as_deref(), Some("OpenSSH server daemon"));
+        assert_eq!(
+            sshd.exec_start.as_deref(),
+            Some("/usr/sbin/sshd -D $OPTIONS")
+        );
+
+        // Masked by an empty file in /etc, overriding the vendor unit
+        assert_eq!(
+            service(&inventory, "cups.service").enablement,
+            UnitEnablement::Masked
+        );
+        assert_eq!(
+            service(&inventory, "dbus.service").enablement,
+            UnitEnablement::Static
+        );
+        let nginx = service(&inventory, "nginx.service");
+        assert_eq!(nginx.enablement, UnitEnablement::Disabled);
+        assert!(!nginx.active);
+    }
+
+    #[test]
+    fn test_timers() {
+        let inventory = collect(&fixture_root()).unwrap().unwrap();
+        let timers: Vec<&ScheduledJob> = inventory
+            .scheduled_jobs
+            .iter()
+            .filter(|j| j.source == JobSource::SystemdTimer)
+            .collect();
+        assert_eq!(timers.len(), 2);
+
+        assert_eq!(timers[0].name, "backup.timer");
+        assert_eq!(timers[0].schedule, "OnCalendar=*-*-* 02:00:00");
+        assert_eq!(timers[0].user.as_deref(), Some("backup"));
+        assert_eq!(
+            timers[0].command.as_deref(),
+            Some("/usr/local/bin/backup.sh --incremental")
+        );
+        assert!(timers[0].enabled);
+
+        assert_eq!(timers[1].name, "fstrim.timer");
+        assert_eq!(timers[1].schedule, "OnCalendar=weekly; OnBootSec=15min");
+        assert_eq!(timers[1].user.as_deref(), Some("root"));
+        assert!(!timers[1].enabled);
+    }
+
+    #[test]
+    fn test_cron_jobs() {
+        let inventory = collect(&fixture_root()).unwrap().unwrap();
+        let cron: Vec<(&str, &str, &str, &str)> = inventory
+            .scheduled_jobs
+            .iter()
+            .filter(|j| j.source == JobSource::Cron)
+            .map(|j| {
+                (
+                    j.name.as_str(),
+                    j.schedule.as_str(),
+                    j.user.as_deref().unwrap(),
+                    j.command.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            cron,
+            vec![
+                (
+                    "/etc/crontab",
+                    "17 * * * *",
+                    "root",
+                    "cd / && run-parts --report /etc/cron.hourly"
+                ),
+                (
+                    "/etc/cron.d/certbot",
+                    "0 */12 * * *",
+                    "root",
+                    "test -x /usr/bin/certbot && certbot -q renew"
+                ),
+                (
+                    "/var/spool/cron/crontabs/alice",
+                    "@reboot",
+                    "alice",
+                    "/home/alice/bin/start-agent"
+                ),
+                (
+                    "/var/spool/cron/crontabs/alice",
+                    "*/15 * * * *",
+                    "alice",
+                    "/home/alice/bin/sync.sh >/dev/null 2>&1"
+                ),
+                (
+                    "/etc/cron.daily/logrotate",
+                    "@daily",
+                    "root",
+                    "/etc/cron.daily/logrotate"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_crontab_skips_malformed_lines() {
+        let jobs = parse_crontab("SHELL=/bin/sh\n* * *\n0 1 * * *\n", "/etc/crontab", None);
+        assert!(jobs.is_empty());
+    }
+
+    #[test]
+    fn test_no_sources() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
@@ -0,0 +1,309 @@
+//! Interactive login sessions from utmp, or from the WMI logon session classes on Windows.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+
+use super::utmp::{self, USER_PROCESS};
+use crate::models::{Session, SessionType};
+
+/// utmp locations relative to the root, newest convention first.
+const UTMP_PATHS: &[&str] = &["run/utmp", "var/run/utmp"];
+
+/// Read the sessions recorded in utmp. `now` is used for idle times.
+pub fn collect(root: &Path, now: DateTime<Utc>) -> Result<Vec<Session>> {
+    let Some(path) = UTMP_PATHS
+        .iter()
+        .map(|p| root.join(p))
+        .find(|p| p.is_file())
+    else {
+        return Ok(Vec::new());
+    };
+    let data =
+        std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    // A crash can leave USER_PROCESS entries behind for processes that are gone
+    let proc = root.join("proc");
+    let check_pids = proc.is_dir();
+
+    Ok(utmp::parse(&data)
+        .into_iter()
+        .filter(|r| r.kind == USER_PROCESS && !r.user.is_empty())
+        .filter(|r| !check_pids || proc.join(r.pid.to_string()).exists())
+        .map(|r| {
+            let (user, domain) = split_domain(&r.user);
+            let session_type = session_type(&r.line, &r.host);
+            let source_host = match session_type {
+                SessionType::Remote => Some(r.host.clone())
+                    .filter(|h| !h.is_empty())
+                    .or(r.addr.map(|a| a.to_string())),
+                _ => None,
+            };
+            Session {
+                user,
+                domain,
+                session_type,
+                terminal: Some(r.line.clone()).filter(|l| !l.is_empty()),
+                login_time: r.time.map(|t| t.to_rfc3339()),
+                idle_seconds: idle_seconds(root, &r.line, now),
+                source_host,
+            }
+        })
+        .collect())
+}
+
+/// A `Win32_LogonSession` instance.
+pub struct WindowsLogon {
+    pub logon_id: String,
+    pub logon_type: u32,
+    /// CIM datetime, e.g. `20251009073000.000000+120`.
+    pub start_time: Option<String>,
+}
+
+/// Interactive sessions from `Win32_LogonSession` and the `(Antecedent, Dependent)` references
+/// of `Win32_LoggedOnUser`, which link accounts to logon sessions. Service, batch and network
+/// logons are skipped; the two tokens Windows creates for an elevated user are reported once.
+pub fn from_windows_logons(
+    logons: &[WindowsLogon],
+    logged_on: &[(String, String)],
+) -> Vec<Session> {
+    let mut sessions: Vec<Session> = logged_on
+        .iter()
+        .filter_map(|(account, session)| {
+            let id = reference_key(session, "LogonId")?;
+            let logon = logons.iter().find(|l| l.logon_id == id)?;
+            let session_type = match logon.logon_type {
+                // Interactive and CachedInteractive
+                2 | 11 => SessionType::Console,
+                // RemoteInteractive (RDP) and CachedRemoteInteractive
+                10 | 12 => SessionType::Remote,
+                _ => return None,
+            };
+            Some(Session {
+                user: reference_key(account, "Name")?,
+                domain: reference_key(account, "Domain"),
+                session_type,
+                terminal: None,
+                login_time: logon.start_time.as_deref().and_then(cim_datetime),
+                idle_seconds: None,
+                source_host: None,
+            })
+        })
+        .collect();
+    sessions.sort_by(|a, b| {
+        (&a.user, &a.domain, a.session_type as u8, &a.login_time).cmp(&(
+            &b.user,
+            &b.domain,
+            b.session_type as u8,
+            &b.login_time,
+        ))
+    });
+    sessions.dedup_by(|a, b| {
+        a.user == b.user && a.domain == b.domain && a.session_type == b.session_type
+    });
+    sessions
+}
+
+/// Quoted key value of a WMI object path such as
+/// `\\.\root\cimv2:Win32_Account.Domain="CORP",Name="jsmith"`.
+fn reference_key(reference: &str, key: &str) -> Option<String> {
+    let (_, keys) = reference.split_once(':')?;
+    let start = keys.find(&format!("{}=\"", key))? + key.len() + 2;
+    let value = &keys[start..];
+    Some(value[..value.find('"')?].to_string())
+}
+
+/// Convert a CIM datetime (`yyyymmddHHMMSS.mmmmmm+UUU`, offset in minutes) to RFC 3339 UTC.
+fn cim_datetime(value: &str) -> Option<String> {
+    let (local, offset) = (value.get(..21)?, value.get(21..)?);
+    let minutes: i32 = offset.parse().ok()?;
+    let time = NaiveDateTime::parse_from_str(local, "%Y%m%d%H%M%S%.f").ok()?;
+    let time = FixedOffset::east_opt(minutes * 60)?
+        .from_local_datetime(&time)
+        .single()?;
+    Some(time.with_timezone(&Utc).to_rfc3339())
+}
+
+/// Split `user@domain` (SSSD) and `DOMAIN\user` (winbind) forms.
+fn split_domain(name: &str) -> (String, Option<String>) {
+    if let Some((domain, user)) = name.split_once('\\') {
+        return (user.to_string(), Some(domain.to_string()));
+    }
+    if let Some((user, domain)) = name.split_once('@') {
+        return (user.to_string(), Some(domain.to_string()));
+    }
+    (name.to_string(), None)
+}
+
+fn session_type(line: &str, host: &str) -> SessionType {
+    // X11 display managers record the display (":0") as the host of a local session
+    if host.starts_with(':') || line.starts_with(':') {
+        return SessionType::Local;
+    }
+    if !host.is_empty() {
+        return SessionType::Remote;
+    }
+    if line == "console" || line.starts_with("tty") {
+        SessionType::Console
+    } else {
+        SessionType::Local
+    }
+}
+
+/// Seconds since the terminal was last read from, as `w` reports it.
+fn idle_seconds(root: &Path, line: &str, now: DateTime<Utc>) -> Option<u64> {
+    if line.is_empty() || line.starts_with(':') {
+        return None;
+    }
+    let accessed = std::fs::metadata(root.join("dev").join(line))
+        .and_then(|m| m.accessed())
+        .ok()?;
+    let idle = now.signed_duration_since(DateTime::<Utc>::from(accessed));
+    Some(idle.num_seconds().max(0) as u64)
+}
+
+/// The user to report as `logged_in_user`: the earliest console session, else the earliest
+/// local one, else the earliest remote one. Users of a NetBIOS domain are formatted as
+/// `DOMAIN\user`, as Windows reports them; users of a DNS domain keep their `user@domain` login
+/// name.
+pub fn primary_user(sessions: &[Session]) -> Option<String> {
+    let rank = |t: SessionType| match t {
+        SessionType::Console => 0,
+        SessionType::Local => 1,
+        SessionType::Remote => 2,
+    };
+    let session = sessions
+        .iter()
+        .enumerate()
+        .min_by_key(|(i, s)| (rank(s.session_type), s.login_time.clone(), *i))?
+        .1;
+    Some(match &session.domain {
+        Some(domain) if domain.contains('.') => format!("{}@{}", session.user, domain),
+        Some(domain) => format!("{}\\{}", domain, session.user),
+        None => session.user.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sessions")
+    }
+
+    #[test]
+    fn test_collect_sessions() {
+        let sessions = collect(&fixture_root(), Utc::now()).unwrap();
+        let users: Vec<_> = sessions.iter().map(|s| s.user.as_str()).collect();
+        // LOGIN/DEAD_PROCESS entries and the stale session of pid 9999 are skipped
+        assert_eq!(users, vec!["alice", "alice", "bob", "carol"]);
+
+        let console = &sessions[0];
+        assert_eq!(console.session_type, SessionType::Console);
+        assert_eq!(console.terminal.as_deref(), Some("tty1"));
+        assert_eq!(
+            console.login_time.as_deref(),
+            Some("2025-10-09T07:30:00+00:00")
+        );
+        assert!(console.idle_seconds.is_some());
+
+        let graphical = &sessions[1];
+        assert_eq!(graphical.session_type, SessionType::Local);
+        assert_eq!(graphical.source_host, None);
+        assert_eq!(graphical.idle_seconds, None);
+
+        let ssh = &sessions[2];
+        assert_eq!(ssh.session_type, SessionType::Remote);
+        assert_eq!(ssh.domain.as_deref(), Some("corp.example.com"));
+        assert_eq!(ssh.source_host.as_deref(), Some("10.20.30.40"));
+
+        let winbind = &sessions[3];
+        assert_eq!(winbind.domain.as_deref(), Some("CORP"));
+        assert_eq!(winbind.session_type, SessionType::Local);
+        assert_eq!(winbind.idle_seconds, None, "no device node in the fixture");
+    }
+
+    #[test]
+    fn test_primary_user() {
+        let sessions = collect(&fixture_root(), Utc::now()).unwrap();
+        assert_eq!(primary_user(&sessions).as_deref(), Some("alice"));
+
+        // A NetBIOS domain is reported in Windows form
+        let winbind: Vec<_> = sessions
+            .iter()
+            .filter(|s| s.user == "carol")
+            .cloned()
+            .collect();
+        assert_eq!(primary_user(&winbind).as_deref(), Some("CORP\\carol"));
+
+        // A DNS domain keeps the login name as recorded
+        let remote: Vec<_> = sessions
+            .into_iter()
+            .filter(|s| s.session_type == SessionType::Remote)
+            .collect();
+        assert_eq!(
+            primary_user(&remote).as_deref(),
+            Some("bob@corp.example.com")
+        );
+        assert_eq!(primary_user(&[]), None);
+    }
+
+    #[test]
+    fn test_windows_logons() {
+        let logon = |id: &str, logon_type: u32, start: &str| WindowsLogon {
+            logon_id: id.to_string(),
+            logon_type,
+            start_time: Some(start.to_string()),
+        };
+        let logons = [
+            logon("997", 5, "20251009062900.000000+120"),
+            logon("3141592", 10, "20251009081500.000000+120"),
+            logon("3141601", 10, "20251009081500.000000+120"),
+            logon("2718281", 3, "20251009090000.000000+120"),
+        ];
+        let link = |domain: &str, name: &str, id: &str| {
+            (
+                format!(
+                    "\\\\.\\root\\cimv2:Win32_Account.Domain=\"{}\",Name=\"{}\"",
+                    domain, name
+                ),
+                format!("\\\\.\\root\\cimv2:Win32_LogonSession.LogonId=\"{}\"", id),
+            )
+        };
+        let logged_on = [
+            link("NT AUTHORITY", "LOCAL SERVICE", "997"),
+            // Elevated RDP user with a split token
+            link("CORP", "jsmith", "3141592"),
+            link("CORP", "jsmith", "3141601"),
+            // SMB client, a network logon
+            link("CORP", "backup", "2718281"),
+        ];
+
+        let sessions = from_windows_logons(&logons, &logged_on);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_type, SessionType::Remote);
+        assert_eq!(
+            sessions[0].login_time.as_deref(),
+            Some("2025-10-09T06:15:00+00:00")
+        );
+        // UserName is empty for RDP-only machines; this is what logged_in_user falls back to
+        assert_eq!(primary_user(&sessions).as_deref(), Some("CORP\\jsmith"));
+    }
+
+    #[test]
+    fn test_session_type() {
+        assert_eq!(session_type("tty2", ""), SessionType::Console);
+        assert_eq!(session_type("pts/0", "192.168.1.9"), SessionType::Remote);
+        assert_eq!(session_type("pts/0", ":0"), SessionType::Local);
+        assert_eq!(session_type("pts/3", ""), SessionType::Local);
+    }
+
+    #[test]
+    fn test_missing_utmp() {
+        let sessions = collect(Path::new("/nonexistent"), Utc::now()).unwrap();
+        assert!(sessions.is_empty());
+    }
+}
@@ -0,0 +1,301 @@
+//! Listening TCP and UDP sockets from `/proc/net`, attributed to processes through the socket
+//! links in `/proc/<pid>/fd`.
+//!
+//! Other users' fd directories are only readable as root; their sockets are listed without a
+//! process.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::read_attr;
+use crate::models::ListeningSocket;
+
+/// `/proc/net` tables, named after the protocol they report.
+const TABLES: &[&str] = &["tcp", "tcp6", "udp", "udp6"];
+
+/// TCP_LISTEN in the `st` column.
+const TCP_LISTEN: &str = "0A";
+/// TCP_CLOSE, the state of UDP sockets that are not connected.
+const UDP_UNCONNECTED: &str = "07";
+
+/// A socket row from a `/proc/net` table.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub address: IpAddr,
+    pub port: u16,
+    pub inode: u64,
+}
+
+/// List listening sockets. Returns an empty list where there is no `/proc/net`.
+pub fn collect(root: &Path) -> Result<Vec<ListeningSocket>> {
+    let proc = root.join("proc");
+    let owners = socket_owners(&proc);
+
+    let mut sockets = Vec::new();
+    for protocol in TABLES {
+        let path = proc.join("net").join(protocol);
+        if !path.is_file() {
+            continue;
+        }
+        let table = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        for entry in parse_table(&table, protocol.starts_with("tcp")) {
+            let owner = owners.get(&entry.inode);
+            sockets.push(ListeningSocket {
+                protocol: protocol.to_string(),
+                local_address: entry.address.to_string(),
+                port: entry.port,
+                pid: owner.map(|(pid, _)| *pid),
+                process_name: owner.and_then(|(_, name)| name.clone()),
+            });
+        }
+    }
+    sockets.sort_by(|a, b| {
+        (&a.protocol, a.port, &a.local_address, a.pid).cmp(&(
+            &b.protocol,
+            b.port,
+            &b.local_address,
+            b.pid,
+        ))
+    });
+    // Forked workers share one socket; SO_REUSEPORT sockets of other processes are kept
+    sockets.dedup_by(|a, b| {
+        a.protocol == b.protocol
+            && a.port == b.port
+            && a.local_address == b.local_address
+            && a.pid == b.pid
+    });
+    Ok(sockets)
+}
+
+/// Parse a `/proc/net/{tcp,udp}[6]` table, keeping listening TCP or unconnected UDP sockets.
+///
+/// Columns: `sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid
+/// timeout inode ...`, with addresses as `ADDR:PORT` in hex.
+pub fn parse_table(table: &str, tcp: bool) -> Vec<Entry> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return None;
+            }
+            let listening = if tcp {
+                fields[3] == TCP_LISTEN
+            } else {
+                fields[3] == UDP_UNCONNECTED && remote_port(fields[2]) == Some(0)
+            };
+            if !listening {
+                return None;
+            }
+            let (address, port) = parse_endpoint(fields[1])?;
+            Some(Entry {
+                address,
+                port,
+                inode: fields[9].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+fn remote_port(endpoint: &str) -> Option<u16> {
+    parse_endpoint(endpoint).map(|(_, port)| port)
+}
+
+/// Decode `0100007F:0035` or a 32-digit IPv6 address. The kernel prints each 32-bit word of
+/// the address in host byte order.
+fn parse_endpoint(endpoint: &str) -> Option<(IpAddr, u16)> {
+    let (addr, port) = endpoint.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for word in addr.as_bytes().chunks(8) {
+        let word = std::str::from_utf8(word).ok()?;
+        bytes.extend(u32::from_str_radix(word, 16).ok()?.to_ne_bytes());
+    }
+    let address = match bytes.len() {
+        4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => {
+            let v6 = Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?);
+            // Dual-stack sockets bound to an IPv4 address show up as ::ffff:a.b.c.d
+            v6.to_ipv4_mapped().map_or(IpAddr::V6(v6), IpAddr::V4)
+        }
+        _ => return None,
+    };
+    Some((address, port))
+}
+
+/// Map socket inodes to the lowest PID holding them and that process's name.
+fn socket_owners(proc: &Path) -> HashMap<u64, (u32, Option<String>)> {
+    let mut owners: HashMap<u64, (u32, Option<String>)> = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(proc) else {
+        return owners;
+    };
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // Permission denied for other users' processes unless running as root
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Some(inode) = std::fs::read_link(fd.path())
+                .ok()
+                .and_then(|target| socket_inode(&target.to_string_lossy()))
+            else {
+                continue;
+            };
+            let owner = owners
+                .entry(inode)
+                .or_insert_with(|| (pid, read_attr(&entry.path(), "comm")));
+            if pid < owner.0 {
+                *owner = (pid, read_attr(&entry.path(), "comm"));
+            }
+        }
+    }
+    owners
+}
+
+/// Inode from an fd link target of the form `socket:[12345]`.
+fn socket_inode(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sockets")
+    }
+
+    fn read_table(name: &str) -> String {
+        std::fs::read_to_string(fixture_root().join("proc/net").join(name)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_tcp_table() {
+        let entries = parse_table(&read_table("tcp"), true);
+        // The established connection is skipped
+        assert_eq!(
+            entries,
+            vec![
+                Entry {
+                    address: "0.0.0.0".parse().unwrap(),
+                    port: 22,
+                    inode: 21334,
+                },
+                Entry {
+                    address: "127.0.0.53".parse().unwrap(),
+                    port: 53,
+                    inode: 18220,
+                },
+                Entry {
+                    address: "127.0.0.1".parse().unwrap(),
+                    port: 631,
+                    inode: 30121,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tcp6_and_udp_tables() {
+        let tcp6 = parse_table(&read_table("tcp6"), true);
+        let addresses: Vec<String> = tcp6.iter().map(|e| e.address.to_string()).collect();
+        assert_eq!(addresses, vec!["::", "::1", "192.168.1.20"]);
+
+        // Only the unconnected sockets
+        let udp = parse_table(&read_table("udp"), false);
+        let ports: Vec<u16> = udp.iter().map(|e| e.port).collect();
+        assert_eq!(ports, vec![53, 5353, 5353]);
+
+        let udp6 = parse_table(&read_table("udp6"), false);
+        assert_eq!(udp6.len(), 1);
+        assert_eq!(udp6[0].address.to_string(), "fe80::1c2b:3cff:fe4d:5e6f");
+    }
+
+    #[test]
+    fn test_socket_inode() {
+        assert_eq!(socket_inode("socket:[21334]"), Some(21334));
+        assert_eq!(socket_inode("pipe:[21334]"), None);
+        assert_eq!(socket_inode("/dev/null"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_with_owners() {
+        use std::os::unix::fs::symlink;
+
+        // fd links are symlinks with ':' in their targets, so the tree is built here
+        let root = std::env::temp_dir().join("inventory-agent-sockets-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let net = root.join("proc/net");
+        std::fs::create_dir_all(&net).unwrap();
+        for table in TABLES {
+            std::fs::write(net.join(table), read_table(table)).unwrap();
+        }
+        for (pid, comm, inodes) in [
+            (812, "sshd", &[21334, 21336][..]),
+            (640, "systemd-resolve", &[18220, 18219][..]),
+            (1501, "sshd", &[21334][..]),
+            (2210, "chrome", &[25007][..]),
+        ] {
+            let dir = root.join(format!("proc/{}", pid));
+            std::fs::create_dir_all(dir.join("fd")).unwrap();
+            std::fs::write(dir.join("comm"), format!("{}\n", comm)).unwrap();
+            for (fd, inode) in inodes.iter().enumerate() {
+                symlink(
+                    format!("socket:[{}]", inode),
+                    dir.join(format!("fd/{}", fd + 3)),
+                )
+                .unwrap();
+            }
+            symlink("/dev/null", dir.join("fd/0")).unwrap();
+        }
+
+        let sockets = collect(&root).unwrap();
+        let summary: Vec<_> = sockets
+            .iter()
+            .map(|s| {
+                (
+                    s.protocol.as_str(),
+                    s.port,
+                    s.local_address.as_str(),
+                    s.pid,
+                    s.process_name.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("tcp", 22, "0.0.0.0", Some(812), Some("sshd")),
+                ("tcp", 53, "127.0.0.53", Some(640), Some("systemd-resolve")),
+                ("tcp", 631, "127.0.0.1", None, None),
+                ("tcp6", 22, "::", Some(812), Some("sshd")),
+                ("tcp6", 443, "192.168.1.20", None, None),
+                ("tcp6", 631, "::1", None, None),
+                ("udp", 53, "127.0.0.53", Some(640), Some("systemd-resolve")),
+                ("udp", 5353, "0.0.0.0", None, None),
+                // Bound with SO_REUSEPORT next to the daemon above
+                ("udp", 5353, "0.0.0.0", Some(2210), Some("chrome")),
+                ("udp6", 5353, "fe80::1c2b:3cff:fe4d:5e6f", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_proc() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_empty());
+    }
+}
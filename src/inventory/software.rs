@@ -0,0 +1,389 @@
+//! Installed software from the dpkg status file and the RPM database.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+
+use super::sqlite::{Database, Value};
+use crate::config::SoftwareConfig;
+use crate::models::{PackageSource, SoftwarePackage};
+
+const DPKG_STATUS: &str = "var/lib/dpkg/status";
+const DPKG_INFO: &str = "var/lib/dpkg/info";
+
+/// RPM 4.16+ sqlite databases; the first one that exists wins since `/var/lib/rpm` is usually a
+/// symlink to the sysimage location.
+const RPMDB_PATHS: &[&str] = &[
+    "usr/lib/sysimage/rpm/rpmdb.sqlite",
+    "var/lib/rpm/rpmdb.sqlite",
+];
+
+/// Older RPM database formats, which are not read: BerkeleyDB (RHEL/CentOS 7 and 8) and ndb
+/// (SUSE).
+const LEGACY_RPMDB_PATHS: &[(&str, &str)] = &[
+    ("var/lib/rpm/Packages", "BerkeleyDB"),
+    ("usr/lib/sysimage/rpm/Packages.db", "ndb"),
+    ("var/lib/rpm/Packages.db", "ndb"),
+];
+
+// RPM header tags and types (see rpmtag.h)
+const RPMTAG_NAME: i32 = 1000;
+const RPMTAG_VERSION: i32 = 1001;
+const RPMTAG_RELEASE: i32 = 1002;
+const RPMTAG_EPOCH: i32 = 1003;
+const RPMTAG_INSTALLTIME: i32 = 1008;
+const RPMTAG_VENDOR: i32 = 1011;
+const RPMTAG_ARCH: i32 = 1022;
+const RPMTAG_SOURCERPM: i32 = 1044;
+const RPM_INT32_TYPE: u32 = 4;
+const RPM_STRING_TYPE: u32 = 6;
+const RPM_I18NSTRING_TYPE: u32 = 9;
+
+/// Collect installed packages from every package database found under `root`.
+///
+/// Each source is read independently: a missing, oversized or corrupt source is logged and
+/// skipped without affecting the others.
+pub fn collect(root: &Path, cfg: &SoftwareConfig) -> Result<Vec<SoftwarePackage>> {
+    if !cfg.enabled {
+        return Ok(Vec::new());
+    }
+
+    let mut packages = Vec::new();
+    packages.extend(super::best_effort(
+        "dpkg",
+        collect_dpkg(root, cfg.max_dpkg_status_bytes),
+    ));
+    packages.extend(super::best_effort(
+        "rpm",
+        collect_rpm(root, cfg.max_rpmdb_bytes),
+    ));
+    packages.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+    Ok(packages)
+}
+
+fn collect_dpkg(root: &Path, max_bytes: u64) -> Result<Vec<SoftwarePackage>> {
+    let Some(data) = read_limited(&root.join(DPKG_STATUS), max_bytes)? else {
+        return Ok(Vec::new());
+    };
+    let mut packages = parse_dpkg_status(&String::from_utf8_lossy(&data));
+
+    // dpkg keeps no install date; the file list is written when the package is unpacked
+    let info = root.join(DPKG_INFO);
+    for pkg in &mut packages {
+        let mut candidates = vec![format!("{}.list", pkg.name)];
+        if let Some(arch) = &pkg.arch {
+            candidates.insert(0, format!("{}:{}.list", pkg.name, arch));
+        }
+        pkg.install_date = candidates.iter().find_map(|file| {
+            let modified = std::fs::metadata(info.join(file)).ok()?.modified().ok()?;
+            Some(DateTime::<Utc>::from(modified).to_rfc3339())
+        });
+    }
+    Ok(packages)
+}
+
+fn collect_rpm(root: &Path, max_bytes: u64) -> Result<Vec<SoftwarePackage>> {
+    let Some(path) = RPMDB_PATHS
+        .iter()
+        .map(|p| root.join(p))
+        .find(|p| p.exists())
+    else {
+        if let Some((path, format)) = legacy_rpmdb(root) {
+            log::warn!(
+                "RPM database {} uses the unsupported {} format; RPM packages are not reported",
+                path,
+                format
+            );
+        }
+        return Ok(Vec::new());
+    };
+    let Some(data) = read_limited(&path, max_bytes)? else {
+        return Ok(Vec::new());
+    };
+    let db =
+        Database::from_bytes(data).with_context(|| format!("failed to open {}", path.display()))?;
+
+    let mut packages = Vec::new();
+    for row in db.table_rows("Packages")? {
+        let Some(Value::Blob(blob)) = row.values.get(1) else {
+            continue;
+        };
+        match parse_rpm_header(blob) {
+            // Imported signing keys are stored as pseudo-packages
+            Ok(pkg) if pkg.name == "gpg-pubkey" => {}
+            Ok(pkg) => packages.push(pkg),
+            Err(e) => log::warn!("skipping RPM header {}: {:#}", row.rowid, e),
+        }
+    }
+    Ok(packages)
+}
+
+/// The first legacy RPM database present and its format.
+fn legacy_rpmdb(root: &Path) -> Option<(&'static str, &'static str)> {
+    LEGACY_RPMDB_PATHS
+        .iter()
+        .find(|(path, _)| root.join(path).is_file())
+        .copied()
+}
+
+/// Read a file, returning `None` if it does not exist and an error if it exceeds `max_bytes`.
+pub(crate) fn read_limited(path: &Path, max_bytes: u64) -> Result<Option<Vec<u8>>> {
+    let file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("failed to open {}", path.display())),
+    };
+    let mut data = Vec::new();
+    file.take(max_bytes + 1)
+        .read_to_end(&mut data)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    if data.len() as u64 > max_bytes {
+        bail!("{} exceeds the {} byte limit", path.display(), max_bytes);
+    }
+    Ok(Some(data))
+}
+
+/// Parse a dpkg status file, keeping only packages that are fully installed.
+pub fn parse_dpkg_status(contents: &str) -> Vec<SoftwarePackage> {
+    let mut packages = Vec::new();
+
+    for stanza in contents.split("\n\n") {
+        let mut name = None;
+        let mut version = None;
+        let mut arch = None;
+        let mut vendor = None;
+        let mut source_package = None;
+        let mut installed = false;
+
+        // Continuation lines start with whitespace and never hold the fields we need
+        for line in stanza.lines().filter(|l| !l.starts_with([' ', '\t'])) {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().to_string();
+            match key {
+                "Package" => name = Some(value),
+                "Version" => version = Some(value),
+                "Architecture" => arch = Some(value),
+                "Maintainer" => vendor = Some(value),
+                // "Source: openssl" or "Source: glibc (2.36-9)" when the versions differ
+                "Source" => source_package = value.split_whitespace().next().map(str::to_string),
+                "Status" => installed = value.split_whitespace().nth(2) == Some("installed"),
+                _ => {}
+            }
+        }
+
+        if let (true, Some(name), Some(version)) = (installed, name, version) {
+            packages.push(SoftwarePackage {
+                name,
+                version,
+                arch,
+                vendor,
+                source: PackageSource::Dpkg,
+                source_package,
+                install_date: None,
+            });
+        }
+    }
+
+    packages
+}
+
+/// Parse an RPM header blob as stored in the database (no lead or magic, starting at the index).
+pub fn parse_rpm_header(blob: &[u8]) -> Result<SoftwarePackage> {
+    let be32 = |off: usize| -> Result<u32> {
+        let b = blob.get(off..off + 4).context("truncated RPM header")?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let index_len = be32(0)? as usize;
+    let data_len = be32(4)? as usize;
+    let data_start = 8 + index_len
+        .checked_mul(16)
+        .context("invalid RPM index length")?;
+    let data = blob
+        .get(data_start..data_start + data_len)
+        .context("truncated RPM header data")?;
+
+    let mut name = None;
+    let mut version = None;
+    let mut release = None;
+    let mut epoch = None;
+    let mut arch = None;
+    let mut vendor = None;
+    let mut install_time = None;
+    let mut source_rpm = None;
+
+    for i in 0..index_len {
+        let entry = 8 + i * 16;
+        let tag = be32(entry)? as i32;
+        let kind = be32(entry + 4)?;
+        let offset = be32(entry + 8)? as usize;
+
+        let string = || -> Option<String> {
+            if kind != RPM_STRING_TYPE && kind != RPM_I18NSTRING_TYPE {
+                return None;
+            }
+            let rest = data.get(offset..)?;
+            let end = rest.iter().position(|b| *b == 0)?;
+            Some(String::from_utf8_lossy(&rest[..end]).into_owned())
+        };
+        let int32 = || -> Option<u32> {
+            if kind != RPM_INT32_TYPE {
+                return None;
+            }
+            let b = data.get(offset..offset + 4)?;
+            Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        };
+
+        match tag {
+            RPMTAG_NAME => name = string(),
+            RPMTAG_VERSION => version = string(),
+            RPMTAG_RELEASE => release = string(),
+            RPMTAG_EPOCH => epoch = int32(),
+            RPMTAG_ARCH => arch = string(),
+            RPMTAG_VENDOR => vendor = string(),
+            RPMTAG_INSTALLTIME => install_time = int32(),
+            RPMTAG_SOURCERPM => source_rpm = string(),
+            _ => {}
+        }
+    }
+
+    let name = name.context("RPM header has no name")?;
+    // "bash-5.2.26-3.fc40.src.rpm" -> "bash"
+    let source_package = source_rpm
+        .as_deref()
+        .and_then(|s| s.rsplitn(3, '-').nth(2))
+        .filter(|s| *s != name)
+        .map(str::to_string);
+    let mut evr = version.context("RPM header has no version")?;
+    if let Some(release) = release {
+        evr = format!("{}-{}", evr, release);
+    }
+    if let Some(epoch) = epoch {
+        evr = format!("{}:{}", epoch, evr);
+    }
+
+    Ok(SoftwarePackage {
+        name,
+        version: evr,
+        arch,
+        vendor,
+        source: PackageSource::Rpm,
+        source_package,
+        install_date: install_time
+            .and_then(|t| DateTime::<Utc>::from_timestamp(t as i64, 0))
+            .map(|t| t.to_rfc3339()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/software")
+            .join(name)
+    }
+
+    #[test]
+    fn test_parse_dpkg_status_skips_removed() {
+        let status = "Package: vim\nStatus: install ok installed\nArchitecture: amd64\nVersion: 2:9.0.1378-2\nMaintainer: Debian Vim Maintainers <team+vim@tracker.debian.org>\nDescription: Vi IMproved\n long description\n\nPackage: nano\nStatus: deinstall ok config-files\nVersion: 7.2-1\n";
+        let pkgs = parse_dpkg_status(status);
+        assert_eq!(pkgs.len(), 1);
+        assert_eq!(pkgs[0].name, "vim");
+        assert_eq!(pkgs[0].version, "2:9.0.1378-2");
+        assert_eq!(pkgs[0].arch.as_deref(), Some("amd64"));
+        assert!(pkgs[0].vendor.as_deref().unwrap().starts_with("Debian Vim"));
+    }
+
+    #[test]
+    fn test_collect_dpkg_fixture() {
+        let pkgs = collect(&fixture_root("debian"), &SoftwareConfig::default()).unwrap();
+        let names: Vec<_> = pkgs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["base-files", "libc6", "openssl"]);
+        assert!(pkgs.iter().all(|p| p.source == PackageSource::Dpkg));
+
+        // Install date comes from the package file list
+        let libc = pkgs.iter().find(|p| p.name == "libc6").unwrap();
+        assert!(libc.install_date.is_some());
+        assert_eq!(libc.source_package.as_deref(), Some("glibc"));
+        let openssl = pkgs.iter().find(|p| p.name == "openssl").unwrap();
+        assert!(openssl.install_date.is_none());
+    }
+
+    #[test]
+    fn test_collect_rpm_fixture() {
+        let pkgs = collect(&fixture_root("fedora"), &SoftwareConfig::default()).unwrap();
+        assert_eq!(pkgs.len(), 2, "gpg-pubkey entries are skipped");
+
+        let bash = &pkgs[0];
+        assert_eq!(bash.name, "bash");
+        assert_eq!(bash.version, "5.2.26-3.fc40");
+        assert_eq!(bash.arch.as_deref(), Some("x86_64"));
+        assert_eq!(bash.vendor.as_deref(), Some("Fedora Project"));
+        assert_eq!(bash.source, PackageSource::Rpm);
+        assert_eq!(
+            bash.install_date.as_deref(),
+            Some("2024-05-01T12:00:00+00:00")
+        );
+
+        // Epoch is folded into the version; the large header spans overflow pages
+        let kernel = &pkgs[1];
+        assert_eq!(kernel.name, "kernel-core");
+        assert_eq!(kernel.version, "1:6.8.9-300.fc40");
+        assert_eq!(kernel.source_package.as_deref(), Some("kernel"));
+    }
+
+    #[test]
+    fn test_size_limit_skips_source() {
+        let cfg = SoftwareConfig {
+            max_dpkg_status_bytes: 16,
+            ..SoftwareConfig::default()
+        };
+        let pkgs = collect(&fixture_root("debian"), &cfg).unwrap();
+        assert!(pkgs.is_empty());
+        assert!(read_limited(&fixture_root("debian").join(DPKG_STATUS), 16).is_err());
+    }
+
+    #[test]
+    fn test_disabled_and_missing_sources() {
+        let cfg = SoftwareConfig {
+            enabled: false,
+            ..SoftwareConfig::default()
+        };
+        assert!(collect(&fixture_root("debian"), &cfg).unwrap().is_empty());
+        assert!(
+            collect(&fixture_root("missing"), &SoftwareConfig::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_legacy_rpmdb_detected() {
+        let root = std::env::temp_dir().join("inventory-agent-legacy-rpmdb-test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("var/lib/rpm")).unwrap();
+        std::fs::write(root.join("var/lib/rpm/Packages"), [0u8; 64]).unwrap();
+        assert_eq!(
+            legacy_rpmdb(&root),
+            Some(("var/lib/rpm/Packages", "BerkeleyDB"))
+        );
+        // Reported as no packages rather than an error
+        assert!(collect(&root, &SoftwareConfig::default())
+            .unwrap()
+            .is_empty());
+        assert_eq!(legacy_rpmdb(&fixture_root("fedora")), None);
+    }
+
+    #[test]
+    fn test_parse_rpm_header_truncated() {
+        assert!(parse_rpm_header(&[0, 0, 0, 5, 0, 0, 0, 0]).is_err());
+        assert!(parse_rpm_header(&[]).is_err());
+    }
+}
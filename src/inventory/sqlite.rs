@@ -0,0 +1,313 @@
+//! Minimal read-only SQLite table reader.
+//!
+//! Only what is needed to pull rows out of rowid tables (such as the RPM `Packages` table) is
+//! implemented: the file header, table b-tree pages, overflow chains and record decoding. Index
+//! b-trees, WITHOUT ROWID tables and the write-ahead log are not read, so changes that have not
+//! been checkpointed into the main database file yet are not visible.
+
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+
+const MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+const PAGE_INTERIOR_TABLE: u8 = 0x05;
+const PAGE_LEAF_TABLE: u8 = 0x0d;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub rowid: i64,
+    pub values: Vec<Value>,
+}
+
+pub struct Database {
+    data: Vec<u8>,
+    page_size: usize,
+    usable_size: usize,
+}
+
+impl Database {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        if data.len() < 100 || &data[..16] != MAGIC {
+            bail!("not an SQLite database");
+        }
+        let page_size = match u16::from_be_bytes([data[16], data[17]]) {
+            1 => 65536,
+            n if n >= 512 && n.is_power_of_two() => n as usize,
+            n => bail!("invalid SQLite page size {}", n),
+        };
+        let reserved = data[20] as usize;
+        if reserved >= page_size - 480 {
+            bail!("invalid SQLite reserved space {}", reserved);
+        }
+        Ok(Self {
+            data,
+            page_size,
+            usable_size: page_size - reserved,
+        })
+    }
+
+    /// Read every row of the named rowid table.
+    pub fn table_rows(&self, table: &str) -> Result<Vec<Row>> {
+        let root = self
+            .walk_table(1)?
+            .into_iter()
+            .find_map(|row| match row.values.as_slice() {
+                [Value::Text(kind), Value::Text(name), _, Value::Integer(root), ..]
+                    if kind == "table" && name == table =>
+                {
+                    Some(*root)
+                }
+                _ => None,
+            })
+            .with_context(|| format!("table {} not found", table))?;
+        let root = u32::try_from(root).context("invalid table root page")?;
+        self.walk_table(root)
+    }
+
+    fn page(&self, number: u32) -> Result<&[u8]> {
+        let start = (number as usize)
+            .checked_sub(1)
+            .context("invalid page number 0")?
+            * self.page_size;
+        self.data
+            .get(start..start + self.page_size)
+            .with_context(|| format!("page {} out of range", number))
+    }
+
+    fn walk_table(&self, root: u32) -> Result<Vec<Row>> {
+        let mut rows = Vec::new();
+        let mut stack = vec![root];
+        let mut visited = HashSet::new();
+
+        while let Some(number) = stack.pop() {
+            if !visited.insert(number) {
+                bail!("b-tree page {} visited twice", number);
+            }
+            let page = self.page(number)?;
+            // Page 1 starts with the 100-byte database header
+            let header = if number == 1 { 100 } else { 0 };
+            let kind = *page.get(header).context("truncated page")?;
+            let cell_count = read_u16(page, header + 3)? as usize;
+
+            match kind {
+                PAGE_LEAF_TABLE => {
+                    for i in 0..cell_count {
+                        let offset = read_u16(page, header + 8 + i * 2)? as usize;
+                        rows.push(self.leaf_cell(page, offset)?);
+                    }
+                }
+                PAGE_INTERIOR_TABLE => {
+                    // Push in reverse so rows come out in rowid order
+                    stack.push(read_u32(page, header + 8)?);
+                    for i in (0..cell_count).rev() {
+                        let offset = read_u16(page, header + 12 + i * 2)? as usize;
+                        stack.push(read_u32(page, offset)?);
+                    }
+                }
+                other => bail!(
+                    "unexpected b-tree page type {:#04x} on page {}",
+                    other,
+                    number
+                ),
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn leaf_cell(&self, page: &[u8], offset: usize) -> Result<Row> {
+        let cell = page.get(offset..).context("cell offset out of range")?;
+        let (payload_len, n1) = read_varint(cell)?;
+        let (rowid, n2) = read_varint(&cell[n1..])?;
+        let payload_len = usize::try_from(payload_len).context("invalid payload length")?;
+        // A payload cannot be larger than the file; checked before allocating for it
+        if payload_len > self.data.len() {
+            bail!("payload length {} exceeds database size", payload_len);
+        }
+        let cell = &cell[n1 + n2..];
+
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        let local = if payload_len <= max_local {
+            payload_len
+        } else {
+            let min_local = (usable - 12) * 32 / 255 - 23;
+            let k = min_local + (payload_len - min_local) % (usable - 4);
+            if k <= max_local {
+                k
+            } else {
+                min_local
+            }
+        };
+
+        let mut payload = Vec::with_capacity(payload_len);
+        payload.extend_from_slice(cell.get(..local).context("truncated cell payload")?);
+
+        if local < payload_len {
+            let mut next = read_u32(cell, local)?;
+            let mut visited = HashSet::new();
+            while payload.len() < payload_len {
+                if next == 0 || !visited.insert(next) {
+                    bail!("broken overflow chain");
+                }
+                let page = self.page(next)?;
+                let take = (payload_len - payload.len()).min(usable - 4);
+                payload.extend_from_slice(&page[4..4 + take]);
+                next = read_u32(page, 0)?;
+            }
+        }
+
+        Ok(Row {
+            rowid: rowid as i64,
+            values: decode_record(&payload)?,
+        })
+    }
+}
+
+fn decode_record(payload: &[u8]) -> Result<Vec<Value>> {
+    let (header_len, mut pos) = read_varint(payload)?;
+    let header_len = header_len as usize;
+    let mut types = Vec::new();
+    while pos < header_len {
+        let (serial, n) = read_varint(
+            payload
+                .get(pos..header_len)
+                .context("truncated record header")?,
+        )?;
+        types.push(serial);
+        pos += n;
+    }
+
+    let mut body = payload.get(header_len..).context("truncated record")?;
+    let mut values = Vec::with_capacity(types.len());
+    for serial in types {
+        let len = match serial {
+            0 | 8 | 9 => 0,
+            1..=4 => serial as usize,
+            5 => 6,
+            6 | 7 => 8,
+            n if n >= 12 => ((n - 12) / 2) as usize,
+            n => bail!("reserved serial type {}", n),
+        };
+        let bytes = body.get(..len).context("truncated record value")?;
+        body = &body[len..];
+
+        values.push(match serial {
+            0 => Value::Null,
+            8 => Value::Integer(0),
+            9 => Value::Integer(1),
+            1..=6 => {
+                // Big-endian two's complement, sign-extended from the stored width
+                let mut v: i64 = if bytes[0] & 0x80 != 0 { -1 } else { 0 };
+                for b in bytes {
+                    v = (v << 8) | *b as i64;
+                }
+                Value::Integer(v)
+            }
+            7 => Value::Real(f64::from_be_bytes(bytes.try_into().expect("8 bytes"))),
+            n if n % 2 == 0 => Value::Blob(bytes.to_vec()),
+            _ => Value::Text(String::from_utf8_lossy(bytes).into_owned()),
+        });
+    }
+    Ok(values)
+}
+
+/// Decode an SQLite varint, returning the value and the number of bytes consumed.
+fn read_varint(buf: &[u8]) -> Result<(u64, usize)> {
+    let mut value: u64 = 0;
+    for i in 0..9 {
+        let b = *buf.get(i).context("truncated varint")?;
+        if i == 8 {
+            return Ok(((value << 8) | b as u64, 9));
+        }
+        value = (value << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    unreachable!()
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> Result<u16> {
+    let b = buf.get(offset..offset + 2).context("truncated page")?;
+    Ok(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> Result<u32> {
+    let b = buf.get(offset..offset + 4).context("truncated page")?;
+    Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = format!(
+            "{}/tests/fixtures/sqlite/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn test_read_varint() {
+        assert_eq!(read_varint(&[0x05]).unwrap(), (5, 1));
+        assert_eq!(read_varint(&[0x81, 0x00]).unwrap(), (128, 2));
+        assert_eq!(read_varint(&[0xff; 9]).unwrap(), (u64::MAX, 9));
+        assert!(read_varint(&[0x81]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_non_sqlite() {
+        assert!(Database::from_bytes(b"not a database at all".repeat(10)).is_err());
+    }
+
+    #[test]
+    fn test_table_rows_with_overflow_and_interior_pages() {
+        let db = Database::from_bytes(fixture("sample.sqlite")).unwrap();
+        let rows = db.table_rows("items").unwrap();
+
+        // Enough rows to force interior pages, in rowid order
+        assert_eq!(rows.len(), 200);
+        assert_eq!(rows[0].rowid, 1);
+        assert_eq!(rows[199].rowid, 200);
+        assert_eq!(rows[0].values[1], Value::Text("item-1".to_string()));
+        assert_eq!(rows[2].values[2], Value::Integer(-3));
+
+        // Row 7 carries a blob spanning several overflow pages
+        match &rows[6].values[3] {
+            Value::Blob(b) => {
+                assert_eq!(b.len(), 10_000);
+                assert!(b.iter().enumerate().all(|(i, v)| *v == (i % 251) as u8));
+            }
+            other => panic!("expected blob, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rejects_oversized_payload() {
+        let db = Database::from_bytes(fixture("sample.sqlite")).unwrap();
+        // Payload length varint of 2^64 - 1, then rowid 1
+        let mut cell = vec![0xff; 9];
+        cell.push(0x01);
+        assert!(db.leaf_cell(&cell, 0).is_err());
+    }
+
+    #[test]
+    fn test_missing_table() {
+        let db = Database::from_bytes(fixture("sample.sqlite")).unwrap();
+        assert!(db.table_rows("nope").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! systemd unit file lookup and parsing shared by the service and security product sections.
+//! The parser also reads other INI-style files such as `sssd.conf` and `smb.conf`.
+
+use std::path::{Path, PathBuf};
+
+use super::read_attr;
+
+/// Directories systemd loads unit files from, highest precedence first.
+pub(crate) const UNIT_DIRS: &[&str] = &[
+    "etc/systemd/system",
+    "run/systemd/system",
+    "usr/lib/systemd/system",
+    "lib/systemd/system",
+];
+
+/// Directories holding the cgroups of system services: cgroup v2, then the systemd hierarchy
+/// of cgroup v1 and hybrid hosts.
+const SYSTEM_SLICES: &[&str] = &[
+    "sys/fs/cgroup/system.slice",
+    "sys/fs/cgroup/systemd/system.slice",
+];
+
+/// Path of the unit file systemd would load for `name`, if any.
+pub(crate) fn find_unit(root: &Path, name: &str) -> Option<PathBuf> {
+    UNIT_DIRS
+        .iter()
+        .map(|d| root.join(d).join(name))
+        .find(|p| p.symlink_metadata().is_ok())
+}
+
+/// A masked unit is a symlink to `/dev/null` or an empty file; systemd refuses to load it.
+pub(crate) fn is_masked(path: &Path) -> bool {
+    std::fs::read_link(path).is_ok_and(|t| t == Path::new("/dev/null"))
+        || std::fs::metadata(path).is_ok_and(|m| m.len() == 0)
+}
+
+/// A service is active while its cgroup, or a cgroup below it, holds processes.
+pub(crate) fn service_active(root: &Path, service: &str) -> bool {
+    SYSTEM_SLICES
+        .iter()
+        .any(|slice| cgroup_populated(&root.join(slice).join(service)))
+}
+
+/// Services with `Delegate=` and cgroup v2's no-internal-processes rule put the processes in
+/// sub-cgroups, leaving the service's own `cgroup.procs` empty.
+fn cgroup_populated(dir: &Path) -> bool {
+    read_attr(dir, "cgroup.procs").is_some()
+        || std::fs::read_dir(dir).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|e| e.file_type().is_ok_and(|t| t.is_dir()) && cgroup_populated(&e.path()))
+        })
+}
+
+/// `(section, key, value)` assignments of a unit file in file order. Continuation lines and
+/// drop-ins are not supported.
+pub(crate) fn parse_unit(text: &str) -> Vec<(String, String, String)> {
+    let mut section = String::new();
+    let mut entries = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            entries.push((
+                section.clone(),
+                key.trim().to_string(),
+                value.trim().to_string(),
+            ));
+        }
+    }
+    entries
+}
+
+/// Last value of `key` in `section`; later assignments override earlier ones.
+pub(crate) fn unit_value<'a>(
+    entries: &'a [(String, String, String)],
+    section: &str,
+    key: &str,
+) -> Option<&'a str> {
+    entries
+        .iter()
+        .rev()
+        .find(|(s, k, _)| s == section && k == key)
+        .map(|(_, _, v)| v.as_str())
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/systemd")
+    }
+
+    #[test]
+    fn test_service_active() {
+        let root = fixture_root();
+        // cgroup v1 systemd hierarchy
+        assert!(service_active(&root, "legacy.service"));
+        // Processes only in a sub-cgroup
+        assert!(service_active(&root, "delegated.service"));
+        assert!(!service_active(&root, "stopped.service"));
+        assert!(!service_active(&root, "missing.service"));
+    }
+}
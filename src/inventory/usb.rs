@@ -0,0 +1,226 @@
+//! USB devices from `/sys/bus/usb/devices`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::read_attr;
+use crate::config::UsbConfig;
+use crate::models::UsbDevice;
+
+const USB_DEVICES: &str = "sys/bus/usb/devices";
+
+const CLASS_PER_INTERFACE: u8 = 0x00;
+const CLASS_HUB: u8 = 0x09;
+
+/// Enumerate USB devices, dropping hubs and excluded devices per `cfg`.
+pub fn collect(root: &Path, cfg: &UsbConfig) -> Result<Vec<UsbDevice>> {
+    let dir = root.join(USB_DEVICES);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    // The directory also links every interface ("1-2:1.0"); only devices have idVendor
+    let mut devices = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to list {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| read_device(&e.path()))
+        .filter(|d| !excluded(d, cfg))
+        .collect::<Vec<_>>();
+    devices.sort_by_key(|d| (d.bus, port_key(d.port_path.as_deref())));
+    Ok(devices)
+}
+
+/// Port path as numbers so that port 10 sorts after port 2.1.
+fn port_key(port_path: Option<&str>) -> Option<Vec<u32>> {
+    port_path.map(|p| p.split('.').filter_map(|n| n.parse().ok()).collect())
+}
+
+fn read_device(path: &Path) -> Option<UsbDevice> {
+    let vendor_id = read_attr(path, "idVendor")?.to_lowercase();
+    let product_id = read_attr(path, "idProduct")?.to_lowercase();
+    let hex = |name: &str| -> Option<u8> { u8::from_str_radix(&read_attr(path, name)?, 16).ok() };
+
+    let device_class = hex("bDeviceClass").unwrap_or(CLASS_PER_INTERFACE);
+    let interface_classes = interface_classes(path);
+
+    // Composite devices declare class 0 and describe themselves per interface
+    let effective_class = match (device_class, interface_classes.first()) {
+        (CLASS_PER_INTERFACE, Some(first)) => *first,
+        _ => device_class,
+    };
+
+    Some(UsbDevice {
+        bus: read_attr(path, "busnum")?.parse().ok()?,
+        device_number: read_attr(path, "devnum").and_then(|v| v.parse().ok()),
+        // Root hubs report devpath "0"
+        port_path: read_attr(path, "devpath").filter(|p| p != "0"),
+        vendor_id,
+        product_id,
+        manufacturer: read_attr(path, "manufacturer"),
+        product: read_attr(path, "product"),
+        serial_number: read_attr(path, "serial"),
+        device_class: format!("{:02x}", effective_class),
+        class_name: class_name(effective_class).map(str::to_string),
+        interface_classes: interface_classes
+            .iter()
+            .map(|c| format!("{:02x}", c))
+            .collect(),
+        usb_version: read_attr(path, "version"),
+        speed_mbps: read_attr(path, "speed"),
+    })
+}
+
+/// Distinct interface classes of the active configuration, in interface order.
+fn interface_classes(device: &Path) -> Vec<u8> {
+    let Ok(entries) = std::fs::read_dir(device) else {
+        return Vec::new();
+    };
+    let mut interfaces = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.join("bInterfaceClass").is_file())
+        .collect::<Vec<_>>();
+    interfaces.sort();
+    let mut classes = Vec::new();
+    for class in interfaces
+        .iter()
+        .filter_map(|p| u8::from_str_radix(&read_attr(p, "bInterfaceClass")?, 16).ok())
+    {
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+    classes
+}
+
+fn excluded(device: &UsbDevice, cfg: &UsbConfig) -> bool {
+    if !cfg.include_hubs && device.device_class == format!("{:02x}", CLASS_HUB) {
+        return true;
+    }
+    if cfg
+        .exclude_classes
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(&device.device_class))
+    {
+        return true;
+    }
+    cfg.exclude_ids
+        .iter()
+        .any(|pattern| id_matches(pattern, &device.vendor_id, &device.product_id))
+}
+
+/// Match `vendor:product` against a `vvvv:pppp` pattern, where either half may be `*`.
+fn id_matches(pattern: &str, vendor: &str, product: &str) -> bool {
+    let Some((pv, pp)) = pattern.split_once(':') else {
+        return false;
+    };
+    let part = |p: &str, id: &str| p == "*" || p.eq_ignore_ascii_case(id);
+    part(pv, vendor) && part(pp, product)
+}
+
+/// Base class names from the USB-IF class code list.
+fn class_name(class: u8) -> Option<&'static str> {
+    Some(match class {
+        0x01 => "Audio",
+        0x02 => "Communications",
+        0x03 => "Human Interface Device",
+        0x05 => "Physical",
+        0x06 => "Image",
+        0x07 => "Printer",
+        0x08 => "Mass Storage",
+        0x09 => "Hub",
+        0x0a => "CDC Data",
+        0x0b => "Smart Card",
+        0x0d => "Content Security",
+        0x0e => "Video",
+        0x0f => "Personal Healthcare",
+        0x10 => "Audio/Video",
+        0x11 => "Billboard",
+        0x12 => "USB Type-C Bridge",
+        0xdc => "Diagnostic",
+        0xe0 => "Wireless Controller",
+        0xef => "Miscellaneous",
+        0xfe => "Application Specific",
+        0xff => "Vendor Specific",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/usb")
+    }
+
+    fn ids(devices: &[UsbDevice]) -> Vec<String> {
+        devices
+            .iter()
+            .map(|d| format!("{}:{}", d.vendor_id, d.product_id))
+            .collect()
+    }
+
+    #[test]
+    fn test_port_key_numeric_order() {
+        let mut paths = vec!["10", "2.1", "2", "1.10", "1.2"];
+        paths.sort_by_key(|p| port_key(Some(p)));
+        assert_eq!(paths, vec!["1.2", "1.10", "2", "2.1", "10"]);
+        assert!(port_key(None) < port_key(Some("1")));
+    }
+
+    // Interface directories are named without ':' so the fixtures check out on Windows; only
+    // their bInterfaceClass file is read
+    #[test]
+    fn test_collect_skips_hubs_by_default() {
+        let devices = collect(&fixture_root(), &UsbConfig::default()).unwrap();
+        assert_eq!(ids(&devices), vec!["17ef:a396", "1050:0407", "046d:0a87"]);
+
+        let dock = &devices[0];
+        assert_eq!(dock.bus, 1);
+        assert_eq!(dock.port_path.as_deref(), Some("2.1"));
+        assert_eq!(dock.manufacturer.as_deref(), Some("Lenovo"));
+        assert_eq!(dock.class_name.as_deref(), Some("Audio"));
+
+        // Composite device: class comes from its interfaces
+        let key = &devices[1];
+        assert_eq!(key.product.as_deref(), Some("YubiKey OTP+FIDO+CCID"));
+        assert_eq!(key.device_class, "03");
+        assert_eq!(key.interface_classes, vec!["03", "0b"]);
+        assert_eq!(key.serial_number, None);
+
+        let headset = &devices[2];
+        assert_eq!(headset.bus, 3);
+        assert_eq!(headset.serial_number.as_deref(), Some("000000000000"));
+        assert_eq!(headset.speed_mbps.as_deref(), Some("12"));
+    }
+
+    #[test]
+    fn test_include_hubs_and_exclusions() {
+        let cfg = UsbConfig {
+            include_hubs: true,
+            exclude_ids: vec!["1d6b:*".to_string(), "046D:0A87".to_string()],
+            exclude_classes: vec!["01".to_string()],
+        };
+        let devices = collect(&fixture_root(), &cfg).unwrap();
+        // Root hubs (1d6b) and the headset by ID, the dock audio by class
+        assert_eq!(ids(&devices), vec!["2109:2817", "1050:0407"]);
+        assert_eq!(devices[0].class_name.as_deref(), Some("Hub"));
+    }
+
+    #[test]
+    fn test_id_patterns() {
+        assert!(id_matches("1050:*", "1050", "0407"));
+        assert!(id_matches("*:0407", "1050", "0407"));
+        assert!(!id_matches("1050:0408", "1050", "0407"));
+        assert!(!id_matches("1050", "1050", "0407"));
+    }
+
+    #[test]
+    fn test_missing_sysfs() {
+        let devices = collect(Path::new("/nonexistent"), &UsbConfig::default()).unwrap();
+        assert!(devices.is_empty());
+    }
+}
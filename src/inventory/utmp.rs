@@ -0,0 +1,135 @@
+//! Reader for Linux utmp/wtmp login records.
+//!
+//! Both files are flat arrays of the glibc `struct utmp`, which has the same 384-byte layout on
+//! every 64-bit Linux architecture (timestamps are stored as 32-bit values for compatibility).
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use chrono::{DateTime, Utc};
+
+pub const RECORD_LEN: usize = 384;
+
+// ut_type values
+pub const RUN_LVL: i16 = 1;
+pub const BOOT_TIME: i16 = 2;
+pub const USER_PROCESS: i16 = 7;
+pub const DEAD_PROCESS: i16 = 8;
+
+const LINE: std::ops::Range<usize> = 8..40;
+const USER: std::ops::Range<usize> = 44..76;
+const HOST: std::ops::Range<usize> = 76..332;
+const TV_SEC: usize = 340;
+const ADDR_V6: std::ops::Range<usize> = 348..364;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub kind: i16,
+    pub pid: i32,
+    /// Terminal name without the `/dev/` prefix (e.g. `tty1`, `pts/0`).
+    pub line: String,
+    pub user: String,
+    pub host: String,
+    pub addr: Option<IpAddr>,
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Parse every complete record in `data`. A trailing partial record is ignored.
+pub fn parse(data: &[u8]) -> Vec<Record> {
+    data.chunks_exact(RECORD_LEN).map(parse_record).collect()
+}
+
+fn parse_record(rec: &[u8]) -> Record {
+    let i32_at = |off: usize| i32::from_le_bytes(rec[off..off + 4].try_into().unwrap());
+    let addr = &rec[ADDR_V6];
+    let addr = if addr[4..].iter().all(|b| *b == 0) {
+        // Only the first word is set for IPv4, in network byte order
+        let v4 = Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]);
+        (!v4.is_unspecified()).then_some(IpAddr::V4(v4))
+    } else {
+        let octets: [u8; 16] = addr.try_into().unwrap();
+        Some(IpAddr::V6(Ipv6Addr::from(octets)))
+    };
+
+    Record {
+        kind: i16::from_le_bytes([rec[0], rec[1]]),
+        pid: i32_at(4),
+        line: c_string(&rec[LINE]),
+        user: c_string(&rec[USER]),
+        host: c_string(&rec[HOST]),
+        addr,
+        time: DateTime::from_timestamp(i32_at(TV_SEC) as u32 as i64, 0)
+            .filter(|t| t.timestamp() != 0),
+    }
+}
+
+/// Fixed-size field, NUL-padded but not necessarily NUL-terminated.
+fn c_string(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Encode a record the way glibc lays it out on disk.
+    pub(crate) fn record(
+        kind: i16,
+        pid: i32,
+        line: &str,
+        user: &str,
+        host: &str,
+        time: i64,
+    ) -> Vec<u8> {
+        let mut rec = vec![0u8; RECORD_LEN];
+        rec[0..2].copy_from_slice(&kind.to_le_bytes());
+        rec[4..8].copy_from_slice(&pid.to_le_bytes());
+        rec[LINE][..line.len()].copy_from_slice(line.as_bytes());
+        rec[USER][..user.len()].copy_from_slice(user.as_bytes());
+        rec[HOST][..host.len()].copy_from_slice(host.as_bytes());
+        rec[TV_SEC..TV_SEC + 4].copy_from_slice(&(time as i32).to_le_bytes());
+        rec
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let mut data = record(
+            USER_PROCESS,
+            4242,
+            "pts/1",
+            "alice",
+            "10.0.0.5",
+            1_760_000_000,
+        );
+        data[ADDR_V6][..4].copy_from_slice(&[10, 0, 0, 5]);
+        let mut v6 = record(USER_PROCESS, 4300, "pts/2", "bob", "", 1_760_000_100);
+        v6[ADDR_V6].copy_from_slice(&"2001:db8::7".parse::<Ipv6Addr>().unwrap().octets());
+        data.extend(v6);
+        data.extend([0u8; 100]);
+
+        let records = parse(&data);
+        assert_eq!(records.len(), 2, "partial trailing record is ignored");
+        assert_eq!(records[0].kind, USER_PROCESS);
+        assert_eq!(records[0].pid, 4242);
+        assert_eq!(records[0].line, "pts/1");
+        assert_eq!(records[0].user, "alice");
+        assert_eq!(records[0].host, "10.0.0.5");
+        assert_eq!(
+            records[0].addr,
+            Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)))
+        );
+        assert_eq!(
+            records[0].time.map(|t| t.to_rfc3339()).as_deref(),
+            Some("2025-10-09T08:53:20+00:00")
+        );
+        assert_eq!(records[1].addr, Some("2001:db8::7".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_full_width_fields() {
+        let user = "a".repeat(32);
+        let records = parse(&record(USER_PROCESS, 1, "tty1", &user, "", 0));
+        assert_eq!(records[0].user, user);
+        assert_eq!(records[0].time, None);
+    }
+}
@@ -0,0 +1,217 @@
+//! Runtime environment classification: bare metal, virtual machine, container or WSL.
+//!
+//! Evidence comes from DMI vendor strings, the `hypervisor` CPU flag, the init process's cgroup
+//! paths and marker files left by container runtimes.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::cpu::cpuinfo_field;
+use super::dmi::DMI_DIR;
+use super::read_attr;
+use crate::models::{EnvironmentKind, RuntimeEnvironment};
+
+/// DMI attribute, substring and the hypervisor it identifies. Checked in order.
+const DMI_HYPERVISORS: &[(&str, &str, &str)] = &[
+    ("sys_vendor", "QEMU", "qemu"),
+    ("product_name", "KVM", "kvm"),
+    ("sys_vendor", "VMware", "vmware"),
+    ("sys_vendor", "innotek GmbH", "virtualbox"),
+    ("product_name", "VirtualBox", "virtualbox"),
+    ("sys_vendor", "Xen", "xen"),
+    // Microsoft also makes Surface hardware, so only the product name is conclusive
+    ("product_name", "Virtual Machine", "hyperv"),
+    // Also on EC2 bare metal instances, which detect_hypervisor skips
+    ("sys_vendor", "Amazon EC2", "amazon"),
+    // Chromebooks and Pixel laptops report sys_vendor "Google" too
+    ("product_name", "Google Compute Engine", "google"),
+    ("sys_vendor", "Parallels", "parallels"),
+    ("sys_vendor", "Bochs", "bochs"),
+    ("bios_vendor", "BHYVE", "bhyve"),
+];
+
+/// Marker files and the container runtime that creates them.
+const CONTAINER_MARKERS: &[(&str, &str)] =
+    &[(".dockerenv", "docker"), ("run/.containerenv", "podman")];
+
+/// cgroup path fragments of the init process and the runtime they belong to.
+const CGROUP_RUNTIMES: &[(&str, &str)] = &[
+    ("/kubepods", "kubernetes"),
+    ("/docker/", "docker"),
+    ("docker-", "docker"),
+    ("libpod-", "podman"),
+    ("/lxc/", "lxc"),
+    ("/lxc.payload", "lxc"),
+];
+
+/// Classify the environment. Returns `None` where there is no `/proc` (non-Linux hosts).
+pub fn collect(root: &Path) -> Result<Option<RuntimeEnvironment>> {
+    if !root.join("proc").is_dir() {
+        return Ok(None);
+    }
+    let mut evidence = Vec::new();
+
+    let osrelease = read_attr(&root.join("proc/sys/kernel"), "osrelease").unwrap_or_default();
+    let wsl = osrelease.to_lowercase().contains("microsoft");
+    if wsl {
+        evidence.push(format!("osrelease:{}", osrelease));
+    }
+
+    let container_runtime = detect_container(root, &mut evidence);
+    let cpuinfo = std::fs::read_to_string(root.join("proc/cpuinfo")).unwrap_or_default();
+    // Set by the hypervisor in CPUID leaf 1, also visible from containers and WSL
+    let cpu_flag = cpuinfo_field(&cpuinfo, "flags")
+        .is_some_and(|flags| flags.split_whitespace().any(|f| f == "hypervisor"));
+    if cpu_flag {
+        evidence.push("cpuinfo:hypervisor".to_string());
+    }
+    let hypervisor = detect_hypervisor(root, &mut evidence);
+
+    let kind = if wsl {
+        EnvironmentKind::Wsl
+    } else if container_runtime.is_some() {
+        EnvironmentKind::Container
+    } else if hypervisor.is_some() || cpu_flag {
+        EnvironmentKind::VirtualMachine
+    } else {
+        EnvironmentKind::BareMetal
+    };
+
+    Ok(Some(RuntimeEnvironment {
+        kind,
+        hypervisor,
+        container_runtime,
+        evidence,
+    }))
+}
+
+fn detect_container(root: &Path, evidence: &mut Vec<String>) -> Option<String> {
+    // Written by systemd-nspawn and other runtimes following the systemd container interface
+    if let Some(name) = read_attr(&root.join("run/systemd"), "container") {
+        evidence.push("file:/run/systemd/container".to_string());
+        return Some(name);
+    }
+    for (marker, runtime) in CONTAINER_MARKERS {
+        if root.join(marker).exists() {
+            evidence.push(format!("file:/{}", marker));
+            return Some(runtime.to_string());
+        }
+    }
+    // cgroup v2 namespaces usually hide the path ("0::/"), so this mostly helps on v1 hosts
+    let cgroup = std::fs::read_to_string(root.join("proc/1/cgroup")).unwrap_or_default();
+    for line in cgroup.lines() {
+        let path = line.splitn(3, ':').nth(2).unwrap_or_default();
+        if let Some((_, runtime)) = CGROUP_RUNTIMES.iter().find(|(f, _)| path.contains(f)) {
+            evidence.push(format!("cgroup:{}", path));
+            return Some(runtime.to_string());
+        }
+    }
+    None
+}
+
+fn detect_hypervisor(root: &Path, evidence: &mut Vec<String>) -> Option<String> {
+    let dmi = root.join(DMI_DIR);
+    // Instance types such as "m5.metal" run without a hypervisor
+    let ec2_metal = read_attr(&dmi, "product_name").is_some_and(|p| p.contains(".metal"));
+    for (attr, needle, hypervisor) in DMI_HYPERVISORS {
+        if *hypervisor == "amazon" && ec2_metal {
+            continue;
+        }
+        if let Some(value) = read_attr(&dmi, attr).filter(|v| v.contains(needle)) {
+            evidence.push(format!("dmi:{}={}", attr, value));
+            return Some(hypervisor.to_string());
+        }
+    }
+    // Xen PV guests have no DMI tables
+    if let Some(kind) = read_attr(&root.join("sys/hypervisor"), "type") {
+        evidence.push(format!("sys/hypervisor:{}", kind));
+        return Some(kind);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/virtualization")
+            .join(name)
+    }
+
+    #[test]
+    fn test_bare_metal() {
+        let env = collect(&fixture("bare_metal")).unwrap().unwrap();
+        assert_eq!(env.kind, EnvironmentKind::BareMetal);
+        assert_eq!(env.hypervisor, None);
+        assert_eq!(env.container_runtime, None);
+        assert!(env.evidence.is_empty());
+    }
+
+    #[test]
+    fn test_kvm_guest() {
+        let env = collect(&fixture("kvm")).unwrap().unwrap();
+        assert_eq!(env.kind, EnvironmentKind::VirtualMachine);
+        assert_eq!(env.hypervisor.as_deref(), Some("qemu"));
+        assert_eq!(
+            env.evidence,
+            vec!["cpuinfo:hypervisor", "dmi:sys_vendor=QEMU"]
+        );
+    }
+
+    #[test]
+    fn test_google_cloud_guest() {
+        let env = collect(&fixture("gce")).unwrap().unwrap();
+        assert_eq!(env.kind, EnvironmentKind::VirtualMachine);
+        assert_eq!(env.hypervisor.as_deref(), Some("google"));
+    }
+
+    #[test]
+    fn test_google_and_amazon_hardware() {
+        // A Chromebook shares the vendor string with Google Compute Engine
+        let chromebook = collect(&fixture("chromebook")).unwrap().unwrap();
+        assert_eq!(chromebook.kind, EnvironmentKind::BareMetal);
+        assert_eq!(chromebook.hypervisor, None);
+
+        let metal = collect(&fixture("ec2_metal")).unwrap().unwrap();
+        assert_eq!(metal.kind, EnvironmentKind::BareMetal);
+        assert_eq!(metal.hypervisor, None);
+        assert!(metal.evidence.is_empty());
+    }
+
+    #[test]
+    fn test_docker_on_vm() {
+        let env = collect(&fixture("docker")).unwrap().unwrap();
+        assert_eq!(env.kind, EnvironmentKind::Container);
+        assert_eq!(env.container_runtime.as_deref(), Some("docker"));
+        // No DMI inside the container, but the CPU flag still shows the VM underneath
+        assert_eq!(env.hypervisor, None);
+        assert!(env.evidence.contains(&"file:/.dockerenv".to_string()));
+        assert!(env.evidence.contains(&"cpuinfo:hypervisor".to_string()));
+    }
+
+    #[test]
+    fn test_kubernetes_cgroup() {
+        let env = collect(&fixture("kubernetes")).unwrap().unwrap();
+        assert_eq!(env.kind, EnvironmentKind::Container);
+        assert_eq!(env.container_runtime.as_deref(), Some("kubernetes"));
+    }
+
+    #[test]
+    fn test_wsl() {
+        let env = collect(&fixture("wsl")).unwrap().unwrap();
+        assert_eq!(env.kind, EnvironmentKind::Wsl);
+        assert_eq!(
+            env.evidence[0],
+            "osrelease:5.15.153.1-microsoft-standard-WSL2"
+        );
+    }
+
+    #[test]
+    fn test_no_proc() {
+        assert!(collect(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
@@ -1,8 +1,13 @@
 // Library exports for testing
 
 pub mod config;
+pub mod inventory;
 pub mod models;
+pub mod osv;
+pub mod sbom;
 pub mod sender;
+pub mod serial;
+pub mod state;
 
 // Note: collector and service modules require Windows-specific APIs and are not exported for cross-platform testing
 #[cfg(target_os = "windows")]
@@ -2,20 +2,25 @@
 //!
 //! Runs as a Windows Service named `InventoryAgent`. Periodically collects inventory data and POSTs
 //! JSON to the configured API endpoint.
-
-#[cfg(target_os = "windows")]
-mod collector;
-mod config;
-mod models;
-mod sender;
-#[cfg(target_os = "windows")]
-mod service;
+//!
+//! `--sbom` prints a CycloneDX SBOM of the installed software and exits; it works on any platform.
 
 use anyhow::Result;
+use std::path::Path;
 #[cfg(target_os = "windows")]
 use std::time::Duration;
 
+use inventory_agent::config::{self, SoftwareConfig};
+#[cfg(target_os = "windows")]
+use inventory_agent::{collector, sender, service, state};
+use inventory_agent::{inventory, sbom};
+
 fn main() -> Result<()> {
+    // SBOM export only reads package databases, so it is available everywhere
+    if std::env::args().any(|arg| arg == "--sbom") {
+        return run_sbom_mode();
+    }
+
     #[cfg(not(target_os = "windows"))]
     {
         eprintln!("Error: This agent only runs on Windows.");
@@ -58,11 +63,15 @@ fn run_debug_mode() -> Result<()> {
         println!("[DEBUG] WARNING: TLS certificate validation is DISABLED (lab mode)");
     }
 
+    let state_path = state::last_checkin_path()?;
+
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(async {
+        let mut last_checkin = state::load_last_checkin(&state_path);
         loop {
             println!("\n[DEBUG] Collecting inventory...");
-            match collector::collect() {
+            let started = chrono::Utc::now();
+            match collector::collect(&cfg, last_checkin) {
                 Ok(checkin) => {
                     println!("[DEBUG] Collected data:");
                     println!("{}", serde_json::to_string_pretty(&checkin).unwrap_or_default());
@@ -70,7 +79,13 @@ fn run_debug_mode() -> Result<()> {
                     if let Some(ref url) = cfg.api_url {
                         println!("\n[DEBUG] Sending check-in...");
                         match sender::send(&checkin, url, cfg.tls_insecure).await {
-                            Ok(_) => println!("[DEBUG] Check-in sent successfully"),
+                            Ok(_) => {
+                                println!("[DEBUG] Check-in sent successfully");
+                                last_checkin = Some(started);
+                                if let Err(e) = state::save_last_checkin(&state_path, started) {
+                                    println!("[DEBUG] Saving check-in time failed: {:#}", e);
+                                }
+                            }
                             Err(e) => println!("[DEBUG] Send failed: {}", e),
                         }
                     }
@@ -102,7 +117,7 @@ fn run_test_mode() -> Result<()> {
     let cfg = config::load_config()?;
 
     println!("Collecting inventory...");
-    let checkin = collector::collect()?;
+    let checkin = collector::collect(&cfg, None)?;
 
     println!("\nCollected data:");
     println!("{}", serde_json::to_string_pretty(&checkin)?);
@@ -120,3 +135,25 @@ fn run_test_mode() -> Result<()> {
 
     Ok(())
 }
+
+/// SBOM mode: print a CycloneDX SBOM of the installed software to stdout.
+/// Run with: cargo run -- --sbom
+fn run_sbom_mode() -> Result<()> {
+    let cfg = config::load_config()?;
+    let root = Path::new("/");
+
+    // An explicit export ignores `software.enabled`
+    let software_cfg = SoftwareConfig {
+        enabled: true,
+        ..cfg.software
+    };
+    let packages = inventory::software::collect(root, &software_cfg)?;
+    let bom = sbom::render(
+        &packages,
+        inventory::os_release::distro_id(root).as_deref(),
+        &chrono::Utc::now().to_rfc3339(),
+    );
+
+    println!("{}", serde_json::to_string_pretty(&bom)?);
+    Ok(())
+}
@@ -1,20 +1,673 @@
 use serde::{Deserialize, Serialize};
 
+use crate::sbom::Bom;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Drive {
     pub model: String,
+    /// Normalized serial; absent when empty, all zeros or a placeholder.
+    pub serial_number: Option<String>,
+    pub device_id: String,
+    /// Serial exactly as reported by the driver.
+    #[serde(default)]
+    pub serial_number_raw: Option<String>,
+}
+
+/// Package database an installed software entry was read from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageSource {
+    Dpkg,
+    Rpm,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SoftwarePackage {
+    pub name: String,
+    /// Full version string including epoch and release where the source has them.
+    pub version: String,
+    pub arch: Option<String>,
+    /// Maintainer (dpkg) or vendor (rpm) string.
+    pub vendor: Option<String>,
+    pub source: PackageSource,
+    /// Source package the binary was built from, when it differs from `name`.
+    #[serde(default)]
+    pub source_package: Option<String>,
+    /// RFC 3339 install timestamp, if the source records one.
+    pub install_date: Option<String>,
+}
+
+/// An OSV advisory that applies to an installed package.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VulnerabilityMatch {
+    /// OSV advisory ID (e.g. `DSA-5678-1`).
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub package: String,
+    pub installed_version: String,
+    /// Ecosystem severity label (e.g. Debian urgency), if the advisory has one.
+    pub severity: Option<String>,
+    pub cvss_vector: Option<String>,
+    /// Lowest fixed version above the installed one; null if no fix is published.
+    pub fixed_version: Option<String>,
+}
+
+/// Unit of a battery's capacity counters, as reported by the driver.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CapacityUnit {
+    #[serde(rename = "mWh")]
+    MilliwattHours,
+    #[serde(rename = "mAh")]
+    MilliampHours,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Battery {
+    /// Kernel name of the supply (e.g. `BAT0`).
+    pub name: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    pub technology: Option<String>,
+    /// Charging state (e.g. `Charging`, `Discharging`, `Full`).
+    pub status: Option<String>,
+    pub capacity_unit: CapacityUnit,
+    pub design_capacity: Option<u64>,
+    pub full_charge_capacity: Option<u64>,
+    pub cycle_count: Option<u64>,
+    /// Full-charge capacity as a percentage of design capacity.
+    pub health_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PowerStatus {
+    /// Whether any AC adapter is online; null if the machine reports none.
+    pub ac_online: Option<bool>,
+    pub batteries: Vec<Battery>,
+}
+
+/// A connected display, identified from its EDID.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Monitor {
+    /// DRM connector name (e.g. `card0-DP-1`).
+    pub connector: String,
+    /// Three-letter PNP manufacturer ID (e.g. `DEL`).
+    pub manufacturer_id: String,
+    pub product_code: u16,
+    pub model_name: Option<String>,
     pub serial_number: Option<String>,
+    pub manufacture_week: Option<u8>,
+    pub manufacture_year: u16,
+    pub native_width: Option<u32>,
+    pub native_height: Option<u32>,
+    pub width_cm: Option<u8>,
+    pub height_cm: Option<u8>,
+}
+
+/// A PCI function. IDs are lowercase hex as printed by `lspci -n`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PciDevice {
+    /// Domain:bus:device.function (e.g. `0000:00:02.0`).
+    pub address: String,
+    pub vendor_id: String,
     pub device_id: String,
+    pub subsystem_vendor_id: Option<String>,
+    pub subsystem_device_id: Option<String>,
+    /// 24-bit class code: class, subclass and programming interface (e.g. `030000`).
+    pub class_code: String,
+    pub revision: Option<String>,
+    /// Kernel driver bound to the device, if any.
+    pub driver: Option<String>,
+    pub vendor_name: Option<String>,
+    pub device_name: Option<String>,
+    pub subsystem_name: Option<String>,
+    pub class_name: Option<String>,
+}
+
+/// A USB device. IDs and class codes are lowercase hex.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UsbDevice {
+    pub bus: u32,
+    pub device_number: Option<u32>,
+    /// Port chain from the root hub (e.g. `2.1` is port 1 of the hub on port 2); null for root hubs.
+    pub port_path: Option<String>,
+    pub vendor_id: String,
+    pub product_id: String,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+    /// Device class, or the first interface's class for composite devices.
+    pub device_class: String,
+    pub class_name: Option<String>,
+    pub interface_classes: Vec<String>,
+    pub usb_version: Option<String>,
+    /// Negotiated speed in Mbit/s as reported by the kernel (e.g. `480`, `1.5`).
+    pub speed_mbps: Option<String>,
+}
+
+/// A Bluetooth device paired with or trusted by one of the machine's adapters.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BluetoothDevice {
+    /// Address of the local adapter the device is known to.
+    pub adapter: String,
+    /// Device address, or its hex HMAC-SHA256 when address hashing is enabled.
+    pub address: String,
+    /// LE address type (`public`, `static`); null for BR/EDR devices.
+    pub address_type: Option<String>,
+    pub name: Option<String>,
+    /// Raw Class of Device (e.g. `0x240404`); null for LE-only devices.
+    pub device_class: Option<String>,
+    /// Major class from the Class of Device, or the LE appearance category.
+    pub major_class: Option<String>,
+    pub paired: bool,
+    pub trusted: bool,
+    pub blocked: bool,
+    /// When BlueZ last updated the device's stored info, typically on connection.
+    pub last_seen: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionType {
+    /// Text console on a virtual terminal.
+    Console,
+    /// Graphical session or terminal on the machine itself.
+    Local,
+    /// Session from another host (SSH, RDP, XDMCP).
+    Remote,
+}
+
+/// An interactive login session.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Session {
+    pub user: String,
+    pub domain: Option<String>,
+    pub session_type: SessionType,
+    /// Terminal or display (e.g. `tty1`, `pts/0`, `:0`).
+    pub terminal: Option<String>,
+    pub login_time: Option<String>,
+    /// Seconds since the terminal last saw input; null for graphical sessions.
+    pub idle_seconds: Option<u64>,
+    /// Host the session comes from; null unless `session_type` is remote.
+    pub source_host: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LoginEventType {
+    Login,
+    Logout,
+    Reboot,
+    Shutdown,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginEvent {
+    pub event: LoginEventType,
+    /// Null for reboots and shutdowns.
+    pub user: Option<String>,
+    pub terminal: Option<String>,
+    pub source_host: Option<String>,
+    pub time: String,
+}
+
+/// Login history since the previous check-in.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginHistory {
+    /// Events in chronological order.
+    pub events: Vec<LoginEvent>,
+    /// Older events were dropped to stay within the configured limit.
+    pub truncated: bool,
+    /// User with the most logins over the last `frequent_user_days` days.
+    pub most_frequent_user: Option<String>,
+    pub frequent_user_days: u32,
+}
+
+/// A local user account. Password hashes are never reported.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LocalAccount {
+    pub name: String,
+    pub uid: u32,
+    pub gid: u32,
+    pub full_name: Option<String>,
+    pub home: String,
+    pub shell: String,
+    /// Login is impossible: a nologin shell or an expired account.
+    pub disabled: bool,
+    /// Password login is locked; null when the shadow file could not be read.
+    pub locked: Option<bool>,
+    /// Date (`YYYY-MM-DD`) of the last password change.
+    pub last_password_change: Option<String>,
+    /// UID below the regular user range (root is not counted as a system account).
+    pub system_account: bool,
+    /// Primary and supplementary groups.
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrivilegedGroup {
+    pub name: String,
+    pub gid: u32,
+    /// Listed members plus users whose primary group this is.
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LocalAccounts {
+    pub users: Vec<LocalAccount>,
+    pub privileged_groups: Vec<PrivilegedGroup>,
+}
+
+/// Encryption state of a mounted volume.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VolumeEncryption {
+    /// Mounted device (e.g. `/dev/mapper/vg-root`).
+    pub device: String,
+    pub mount_point: String,
+    pub filesystem: String,
+    /// Mounted at `/`.
+    pub system_volume: bool,
+    /// The device or one below it is a dm-crypt mapping.
+    pub encrypted: bool,
+    /// `LUKS1`, `LUKS2`, `plain dm-crypt`, `BitLocker` or `TrueCrypt/VeraCrypt`.
+    pub scheme: Option<String>,
+    /// Cipher from the LUKS header (e.g. `aes-xts-plain64`).
+    pub cipher: Option<String>,
+    pub luks_uuid: Option<String>,
+    /// Block device holding the encrypted data (e.g. `nvme0n1p3`).
+    pub container_device: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiskEncryption {
+    pub volumes: Vec<VolumeEncryption>,
+    /// Whether the volume mounted at `/` is encrypted; null if it was not found.
+    pub system_volume_encrypted: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BootMode {
+    Uefi,
+    Legacy,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tpm {
+    /// `2.0` or `1.2`.
+    pub version: Option<String>,
+    pub manufacturer: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FirmwareSecurity {
+    pub boot_mode: BootMode,
+    /// Null when booted in legacy mode or the EFI variable could not be read.
+    pub secure_boot: Option<bool>,
+    /// Platform key not enrolled; Secure Boot cannot be enforced in setup mode.
+    pub setup_mode: Option<bool>,
+    pub tpm: Option<Tpm>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FirewallBackend {
+    Nftables,
+    Iptables,
+}
+
+/// A base chain, i.e. one attached to a netfilter hook.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FirewallChain {
+    pub family: String,
+    pub table: String,
+    pub name: String,
+    pub hook: String,
+    pub policy: Option<String>,
+    pub rule_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FirewallStatus {
+    pub backend: FirewallBackend,
+    /// Any rule is loaded or a base chain drops by default.
+    pub active: bool,
+    /// `drop` if any input chain drops unmatched traffic, else `accept`; null without input
+    /// chains.
+    pub default_input_policy: Option<String>,
+    pub rule_count: usize,
+    /// Front end that generated the ruleset: `firewalld` or `ufw`.
+    pub manager: Option<String>,
+    /// The configured marker appears in a table name or rule comment.
+    pub managed_marker_present: bool,
+    pub chains: Vec<FirewallChain>,
+}
+
+/// Presence of one product from the security product catalog.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SecurityProduct {
+    pub name: String,
+    pub category: Option<String>,
+    pub installed: bool,
+    pub running: bool,
+    /// Version of the matched package, if the product was found through one.
+    pub version: Option<String>,
+    /// What matched, e.g. `package:falcon-sensor` or `process:falcon-sensor`.
+    #[serde(default)]
+    pub evidence: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatchStatus {
+    /// `apt` or `dnf`.
+    pub package_manager: String,
+    /// RFC 3339 end of the latest successful package upgrade.
+    pub last_update: Option<String>,
+    pub days_since_update: Option<i64>,
+    /// Upgradable packages, where the OS caches a count (Ubuntu update-notifier).
+    pub pending_updates: Option<u32>,
+    pub pending_security_updates: Option<u32>,
+    pub reboot_required: bool,
+    /// Packages that asked for the reboot.
+    #[serde(default)]
+    pub reboot_required_packages: Vec<String>,
+}
+
+/// A TCP socket in LISTEN state or an unconnected UDP socket.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListeningSocket {
+    /// `tcp`, `tcp6`, `udp` or `udp6`.
+    pub protocol: String,
+    pub local_address: String,
+    pub port: u16,
+    /// Owning process; null when its fd table is not readable.
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub uid: Option<u32>,
+    pub user: Option<String>,
+    /// Null when the executable link is not readable.
+    pub executable: Option<String>,
+    /// RFC 3339 start time.
+    pub start_time: Option<String>,
+    /// Average CPU use over the process lifetime, in percent of one core.
+    pub cpu_percent: Option<f64>,
+    /// Resident memory.
+    pub memory_bytes: Option<u64>,
+    /// Matches `processes.deny_executables`.
+    pub flagged: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessSnapshot {
+    /// Processes running, before the size cap.
+    pub total_processes: usize,
+    pub truncated: bool,
+    pub processes: Vec<ProcessInfo>,
+}
+
+/// `systemctl is-enabled` state of a unit.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitEnablement {
+    Enabled,
+    Disabled,
+    /// No `[Install]` section; started only as a dependency of other units.
+    Static,
+    Masked,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceUnit {
+    pub name: String,
+    pub description: Option<String>,
+    pub enablement: UnitEnablement,
+    pub active: bool,
+    pub exec_start: Option<String>,
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobSource {
+    Cron,
+    SystemdTimer,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledJob {
+    pub source: JobSource,
+    /// Crontab path or timer unit name.
+    pub name: String,
+    /// Cron expression, `@daily` style shorthand, or the timer's `On*=` settings.
+    pub schedule: String,
+    pub user: Option<String>,
+    pub command: Option<String>,
+    /// Cron jobs are always enabled; timers when enabled with systemctl.
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceInventory {
+    pub services: Vec<ServiceUnit>,
+    pub scheduled_jobs: Vec<ScheduledJob>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VulnerabilityStatus {
+    NotAffected,
+    Mitigated,
+    Vulnerable,
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CpuVulnerability {
+    /// Kernel name, e.g. `spectre_v2`.
+    pub name: String,
+    pub status: VulnerabilityStatus,
+    /// Kernel text, e.g. `Mitigation: Retpolines; IBPB: conditional`.
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CpuSecurity {
+    pub vendor: Option<String>,
+    pub model_name: Option<String>,
+    /// Loaded microcode revision, e.g. `0xf4`.
+    pub microcode_revision: Option<String>,
+    pub any_vulnerable: bool,
+    pub vulnerabilities: Vec<CpuVulnerability>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvironmentKind {
+    BareMetal,
+    VirtualMachine,
+    Container,
+    Wsl,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RuntimeEnvironment {
+    pub kind: EnvironmentKind,
+    /// e.g. `kvm`, `vmware`, `hyperv`; absent when only the CPU flag shows a hypervisor.
+    pub hypervisor: Option<String>,
+    /// e.g. `docker`, `podman`, `kubernetes`, `lxc`.
+    pub container_runtime: Option<String>,
+    /// What the classification is based on, e.g. `dmi:sys_vendor=QEMU`, `file:/.dockerenv`.
+    pub evidence: Vec<String>,
+}
+
+/// SMBIOS system, board and BIOS identification. Placeholder values are reported as absent.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SystemIdentity {
+    pub manufacturer: Option<String>,
+    pub product_name: Option<String>,
+    pub product_version: Option<String>,
+    pub product_family: Option<String>,
+    pub sku: Option<String>,
+    /// Lowercase SMBIOS system UUID.
+    pub uuid: Option<String>,
+    pub board_vendor: Option<String>,
+    pub board_name: Option<String>,
+    pub bios_vendor: Option<String>,
+    pub bios_version: Option<String>,
+    /// `YYYY-MM-DD`, or the firmware's text when it is not a valid SMBIOS date.
+    pub bios_release_date: Option<String>,
+}
+
+/// Where `CheckIn::hostname` was taken from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HostnameSource {
+    /// `COMPUTERNAME`
+    Environment,
+    /// `Win32_ComputerSystem.DNSHostName`
+    Wmi,
+    EtcHostname,
+    KernelHostname,
+    /// No source named the host; the primary IP address is used.
+    Fallback,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DirectoryKind {
+    ActiveDirectory,
+    Ipa,
+    Ldap,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DirectoryMembership {
+    pub kind: DirectoryKind,
+    /// `sssd`, `winbind` or `windows`.
+    pub client: String,
+    /// DNS domain, lowercase.
+    pub domain: Option<String>,
+    /// Kerberos realm, uppercase.
+    pub realm: Option<String>,
+    /// A machine keytab or secrets database exists, i.e. the host was joined rather than only
+    /// configured.
+    pub machine_credentials: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HostIdentity {
+    /// Short host name, as in `CheckIn::hostname`.
+    pub hostname: String,
+    pub hostname_source: HostnameSource,
+    /// Lowercase fully qualified name.
+    pub fqdn: Option<String>,
+    pub dns_domain: Option<String>,
+    /// `default_realm` from krb5.conf.
+    pub kerberos_realm: Option<String>,
+    pub directory: Option<DirectoryMembership>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Gateway {
+    pub interface: String,
+    /// Next hop; `None` for routes straight onto a point-to-point link such as WireGuard or PPP.
+    pub address: Option<String>,
+    /// Route metric; lower is preferred.
+    pub metric: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VpnInterface {
+    pub name: String,
+    /// e.g. `wireguard`, `tun`, `ppp`, `tailscale`, `anyconnect`.
+    pub kind: String,
+    pub mtu: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NetworkConfiguration {
+    /// IPv4 and IPv6 default routes, preferred first.
+    pub default_gateways: Vec<Gateway>,
+    /// Upstream servers; the systemd-resolved stub address is replaced by its upstreams.
+    pub dns_servers: Vec<String>,
+    pub search_domains: Vec<String>,
+    /// resolv.conf points at the systemd-resolved stub listener.
+    pub systemd_resolved_stub: bool,
+    /// VPN interfaces that are up.
+    pub vpn_interfaces: Vec<VpnInterface>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CheckIn {
     pub hostname: String,
     pub ip_address: String,
     pub logged_in_user: Option<String>,
+    /// Normalized BIOS serial, "UNKNOWN" when absent or rejected.
     pub laptop_serial: String,
     pub drives: Vec<Drive>,
     pub timestamp_utc: String,
+    #[serde(default)]
+    pub software: Vec<SoftwarePackage>,
+    /// CycloneDX rendering of `software`, present when `software.attach_sbom` is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sbom: Option<Bom>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<VulnerabilityMatch>,
+    #[serde(default)]
+    pub power: Option<PowerStatus>,
+    #[serde(default)]
+    pub monitors: Vec<Monitor>,
+    #[serde(default)]
+    pub pci_devices: Vec<PciDevice>,
+    #[serde(default)]
+    pub usb_devices: Vec<UsbDevice>,
+    #[serde(default)]
+    pub bluetooth_devices: Vec<BluetoothDevice>,
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+    #[serde(default)]
+    pub login_history: Option<LoginHistory>,
+    #[serde(default)]
+    pub local_accounts: Option<LocalAccounts>,
+    #[serde(default)]
+    pub disk_encryption: Option<DiskEncryption>,
+    #[serde(default)]
+    pub firmware_security: Option<FirmwareSecurity>,
+    #[serde(default)]
+    pub firewall: Option<FirewallStatus>,
+    #[serde(default)]
+    pub security_products: Vec<SecurityProduct>,
+    #[serde(default)]
+    pub patch_status: Option<PatchStatus>,
+    #[serde(default)]
+    pub listening_sockets: Vec<ListeningSocket>,
+    #[serde(default)]
+    pub processes: Option<ProcessSnapshot>,
+    #[serde(default)]
+    pub services: Option<ServiceInventory>,
+    #[serde(default)]
+    pub cpu_security: Option<CpuSecurity>,
+    #[serde(default)]
+    pub environment: Option<RuntimeEnvironment>,
+    #[serde(default)]
+    pub system_identity: Option<SystemIdentity>,
+    /// BIOS serial exactly as reported by the firmware.
+    #[serde(default)]
+    pub laptop_serial_raw: Option<String>,
+    #[serde(default)]
+    pub host_identity: Option<HostIdentity>,
+    #[serde(default)]
+    pub network: Option<NetworkConfiguration>,
 }
 
 #[cfg(test)]
@@ -27,6 +680,7 @@ mod tests {
             model: "Test Drive".to_string(),
             serial_number: Some("SN123".to_string()),
             device_id: "DRIVE0".to_string(),
+            serial_number_raw: Some("  SN123  ".to_string()),
         };
 
         let cloned = drive.clone();
@@ -43,6 +697,7 @@ mod tests {
             laptop_serial: "ABC123".to_string(),
             drives: vec![],
             timestamp_utc: "2025-12-18T10:00:00Z".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&checkin).unwrap();
@@ -62,9 +717,43 @@ mod tests {
                     model: "TestDrive".to_string(),
                     serial_number: None,
                     device_id: "DEVICE0".to_string(),
+                    serial_number_raw: None,
                 }
             ],
             timestamp_utc: "2025-12-18T12:00:00Z".to_string(),
+            software: vec![SoftwarePackage {
+                name: "openssl".to_string(),
+                version: "3.0.11-1~deb12u2".to_string(),
+                arch: Some("amd64".to_string()),
+                vendor: None,
+                source: PackageSource::Dpkg,
+                source_package: None,
+                install_date: None,
+            }],
+            sbom: None,
+            vulnerabilities: vec![],
+            power: None,
+            monitors: vec![],
+            pci_devices: vec![],
+            usb_devices: vec![],
+            bluetooth_devices: vec![],
+            sessions: vec![],
+            login_history: None,
+            local_accounts: None,
+            disk_encryption: None,
+            firmware_security: None,
+            firewall: None,
+            security_products: vec![],
+            patch_status: None,
+            listening_sockets: vec![],
+            processes: None,
+            services: None,
+            cpu_security: None,
+            environment: None,
+            system_identity: None,
+            laptop_serial_raw: None,
+            host_identity: None,
+            network: None,
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -72,5 +761,45 @@ mod tests {
 
         assert_eq!(original.hostname, parsed.hostname);
         assert_eq!(original.drives.len(), parsed.drives.len());
+        assert_eq!(parsed.software[0].source, PackageSource::Dpkg);
+    }
+
+    #[test]
+    fn test_checkin_without_sections_deserializes() {
+        // Payloads from older agents carry no inventory sections
+        let json = r#"{
+            "hostname": "OLD-AGENT",
+            "ip_address": "10.0.0.2",
+            "logged_in_user": null,
+            "laptop_serial": "SERIAL",
+            "drives": [],
+            "timestamp_utc": "2025-12-18T12:00:00Z"
+        }"#;
+        let parsed: CheckIn = serde_json::from_str(json).unwrap();
+        assert!(parsed.software.is_empty());
+        assert!(parsed.sbom.is_none());
+        assert!(parsed.vulnerabilities.is_empty());
+        assert!(parsed.power.is_none());
+        assert!(parsed.monitors.is_empty());
+        assert!(parsed.pci_devices.is_empty());
+        assert!(parsed.usb_devices.is_empty());
+        assert!(parsed.bluetooth_devices.is_empty());
+        assert!(parsed.sessions.is_empty());
+        assert!(parsed.login_history.is_none());
+        assert!(parsed.local_accounts.is_none());
+        assert!(parsed.disk_encryption.is_none());
+        assert!(parsed.firmware_security.is_none());
+        assert!(parsed.firewall.is_none());
+        assert!(parsed.security_products.is_empty());
+        assert!(parsed.patch_status.is_none());
+        assert!(parsed.listening_sockets.is_empty());
+        assert!(parsed.processes.is_none());
+        assert!(parsed.services.is_none());
+        assert!(parsed.cpu_security.is_none());
+        assert!(parsed.environment.is_none());
+        assert!(parsed.system_identity.is_none());
+        assert!(parsed.laptop_serial_raw.is_none());
+        assert!(parsed.host_identity.is_none());
+        assert!(parsed.network.is_none());
     }
 }
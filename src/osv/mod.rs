@@ -0,0 +1,508 @@
+//! Offline vulnerability matching against a local OSV database export.
+//!
+//! The database is the zip of OSV JSON advisories published per ecosystem (for example
+//! `https://osv-vulnerabilities.storage.googleapis.com/Debian/all.zip`), provisioned on the
+//! endpoint by other means. Nothing is fetched over the network.
+
+pub mod version;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::VulnerabilityConfig;
+use crate::inventory::os_release;
+use crate::models::{PackageSource, SoftwarePackage, VulnerabilityMatch};
+
+#[derive(Debug, Deserialize)]
+struct Advisory {
+    id: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    severity: Vec<SeverityEntry>,
+    #[serde(default)]
+    affected: Vec<Affected>,
+    #[serde(default)]
+    database_specific: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SeverityEntry {
+    #[serde(rename = "type")]
+    kind: String,
+    score: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Affected {
+    package: AffectedPackage,
+    #[serde(default)]
+    ranges: Vec<Range>,
+    #[serde(default)]
+    versions: Vec<String>,
+    #[serde(default)]
+    ecosystem_specific: Option<Value>,
+    #[serde(default)]
+    database_specific: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AffectedPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Range {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Event {
+    Introduced(String),
+    Fixed(String),
+    LastAffected(String),
+    Limit(String),
+}
+
+/// The OSV ecosystem installed packages belong to, e.g. `Debian` release `12`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ecosystem {
+    pub name: String,
+    pub release: Option<String>,
+}
+
+impl Ecosystem {
+    /// Parse an ecosystem string such as `Debian:12` or `Ubuntu:22.04:LTS`.
+    pub fn parse(s: &str) -> Self {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default().to_string();
+        // The release is the first component that looks like a version; this skips
+        // qualifiers such as `Red Hat:enterprise_linux:9`
+        let release = parts
+            .find(|p| p.starts_with(|c: char| c.is_ascii_digit()))
+            .map(str::to_string);
+        Self { name, release }
+    }
+
+    /// Derive the ecosystem from os-release under `root`.
+    pub fn detect(root: &Path) -> Option<Self> {
+        let contents = os_release::read(root)?;
+        let id = os_release::field(&contents, "ID")?;
+        let name = match id.as_str() {
+            "debian" => "Debian",
+            "ubuntu" => "Ubuntu",
+            "almalinux" => "AlmaLinux",
+            "rocky" => "Rocky Linux",
+            "rhel" => "Red Hat",
+            "sles" | "sled" => "SUSE",
+            "mageia" => "Mageia",
+            id if id.starts_with("opensuse") => "openSUSE",
+            _ => return None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            release: os_release::field(&contents, "VERSION_ID"),
+        })
+    }
+
+    /// Whether an advisory's ecosystem string applies to this ecosystem.
+    ///
+    /// Releases match on the major version too, so `AlmaLinux:9` applies to `9.3`. A release on
+    /// only one side is no match: advisories for one release say nothing about the others.
+    pub fn matches(&self, advisory_ecosystem: &str) -> bool {
+        let other = Self::parse(advisory_ecosystem);
+        if other.name != self.name {
+            return false;
+        }
+        match (&self.release, &other.release) {
+            (Some(ours), Some(theirs)) => {
+                ours == theirs || ours.starts_with(&format!("{}.", theirs))
+            }
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Match installed packages against the configured OSV database.
+///
+/// Returns no matches when no database is configured or the ecosystem cannot be determined.
+pub fn collect(
+    root: &Path,
+    packages: &[SoftwarePackage],
+    cfg: &VulnerabilityConfig,
+) -> Result<Vec<VulnerabilityMatch>> {
+    let Some(database) = &cfg.osv_database else {
+        return Ok(Vec::new());
+    };
+    let ecosystem = match &cfg.ecosystem {
+        Some(e) => Ecosystem::parse(e),
+        None => match Ecosystem::detect(root) {
+            Some(e) => e,
+            None => {
+                log::warn!("no OSV ecosystem for this distribution; set vulnerabilities.ecosystem");
+                return Ok(Vec::new());
+            }
+        },
+    };
+    match_database(database, packages, &ecosystem, cfg.max_advisory_bytes)
+}
+
+/// Scan every advisory in the OSV zip at `path` for entries affecting `packages`.
+pub fn match_database(
+    path: &Path,
+    packages: &[SoftwarePackage],
+    ecosystem: &Ecosystem,
+    max_advisory_bytes: u64,
+) -> Result<Vec<VulnerabilityMatch>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("failed to open OSV database {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file))
+        .with_context(|| format!("failed to read OSV database {}", path.display()))?;
+
+    // OSV names Debian and RPM advisories by source package, so index both names
+    let mut by_name: HashMap<&str, Vec<&SoftwarePackage>> = HashMap::new();
+    for pkg in packages {
+        by_name.entry(pkg.name.as_str()).or_default().push(pkg);
+        if let Some(source) = &pkg.source_package {
+            by_name.entry(source.as_str()).or_default().push(pkg);
+        }
+    }
+
+    let mut matches = Vec::new();
+    let mut seen = HashSet::new();
+    let mut contents = String::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() || !entry.name().ends_with(".json") {
+            continue;
+        }
+        if entry.size() > max_advisory_bytes {
+            log::warn!("skipping oversized OSV advisory {}", entry.name());
+            continue;
+        }
+
+        contents.clear();
+        if let Err(e) = (&mut entry)
+            .take(max_advisory_bytes)
+            .read_to_string(&mut contents)
+        {
+            log::warn!("skipping unreadable OSV advisory {}: {}", entry.name(), e);
+            continue;
+        }
+        let advisory: Advisory = match serde_json::from_str(&contents) {
+            Ok(a) => a,
+            Err(e) => {
+                log::warn!("skipping malformed OSV advisory {}: {}", entry.name(), e);
+                continue;
+            }
+        };
+
+        for affected in &advisory.affected {
+            if !ecosystem.matches(&affected.package.ecosystem) {
+                continue;
+            }
+            let Some(candidates) = by_name.get(affected.package.name.as_str()) else {
+                continue;
+            };
+            for pkg in candidates {
+                let Some(fixed_version) = affected_by(pkg, affected) else {
+                    continue;
+                };
+                if !seen.insert((advisory.id.clone(), pkg.name.clone())) {
+                    continue;
+                }
+                let (severity, cvss_vector) = severity(&advisory, affected);
+                matches.push(VulnerabilityMatch {
+                    id: advisory.id.clone(),
+                    aliases: advisory.aliases.clone(),
+                    package: pkg.name.clone(),
+                    installed_version: pkg.version.clone(),
+                    severity,
+                    cvss_vector,
+                    fixed_version,
+                });
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| a.package.cmp(&b.package).then(a.id.cmp(&b.id)));
+    Ok(matches)
+}
+
+/// If `pkg` is affected, return the version that fixes it (`Some(None)` if there is none).
+fn affected_by(pkg: &SoftwarePackage, affected: &Affected) -> Option<Option<String>> {
+    let cmp = |a: &str, b: &str| version::compare(pkg.source, a, b);
+    let installed = pkg.version.as_str();
+
+    if affected
+        .versions
+        .iter()
+        .any(|v| cmp(v, installed) == Ordering::Equal)
+    {
+        return Some(first_fix_after(affected, installed, pkg.source));
+    }
+
+    for range in affected.ranges.iter().filter(|r| r.kind == "ECOSYSTEM") {
+        // Replay the events in version order up to the installed version
+        let mut events: Vec<&Event> = range.events.iter().collect();
+        events.sort_by(|a, b| match (event_version(a), event_version(b)) {
+            ("0", "0") => Ordering::Equal,
+            ("0", _) => Ordering::Less,
+            (_, "0") => Ordering::Greater,
+            (a, b) => cmp(a, b),
+        });
+
+        let mut vulnerable = false;
+        for event in events {
+            let at = event_version(event);
+            if at != "0" && cmp(at, installed) == Ordering::Greater {
+                break;
+            }
+            match event {
+                Event::Introduced(_) => vulnerable = true,
+                Event::Fixed(_) => vulnerable = false,
+                Event::LastAffected(v) => {
+                    vulnerable = vulnerable && cmp(v, installed) == Ordering::Equal
+                }
+                Event::Limit(_) => {}
+            }
+        }
+        if vulnerable {
+            return Some(first_fix_after(affected, installed, pkg.source));
+        }
+    }
+
+    None
+}
+
+fn event_version(event: &Event) -> &str {
+    match event {
+        Event::Introduced(v) | Event::Fixed(v) | Event::LastAffected(v) | Event::Limit(v) => v,
+    }
+}
+
+/// The lowest `fixed` event above the installed version across the ecosystem ranges. `GIT`
+/// and `SEMVER` ranges hold commits and upstream versions, which package ordering cannot compare.
+fn first_fix_after(affected: &Affected, installed: &str, source: PackageSource) -> Option<String> {
+    affected
+        .ranges
+        .iter()
+        .filter(|r| r.kind == "ECOSYSTEM")
+        .flat_map(|r| &r.events)
+        .filter_map(|e| match e {
+            Event::Fixed(v) => Some(v.as_str()),
+            _ => None,
+        })
+        .filter(|v| version::compare(source, v, installed) == Ordering::Greater)
+        .min_by(|a, b| version::compare(source, a, b))
+        .map(str::to_string)
+}
+
+/// Pick a severity label and CVSS vector from the places ecosystems put them.
+fn severity(advisory: &Advisory, affected: &Affected) -> (Option<String>, Option<String>) {
+    let label_from = |v: &Option<Value>, key: &str| -> Option<String> {
+        v.as_ref()?.get(key)?.as_str().map(str::to_string)
+    };
+
+    let label = label_from(&advisory.database_specific, "severity")
+        .or_else(|| label_from(&affected.ecosystem_specific, "urgency"))
+        .or_else(|| label_from(&affected.ecosystem_specific, "severity"))
+        .or_else(|| label_from(&affected.database_specific, "severity"))
+        .or_else(|| {
+            advisory
+                .severity
+                .iter()
+                .find(|s| !s.kind.starts_with("CVSS"))
+                .map(|s| s.score.clone())
+        });
+    let cvss = advisory
+        .severity
+        .iter()
+        .filter(|s| s.kind.starts_with("CVSS"))
+        .max_by(|a, b| a.kind.cmp(&b.kind))
+        .map(|s| s.score.clone());
+
+    (label, cvss)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/osv")
+            .join(name)
+    }
+
+    fn package(name: &str, version: &str, source: PackageSource) -> SoftwarePackage {
+        SoftwarePackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: None,
+            vendor: None,
+            source,
+            source_package: None,
+            install_date: None,
+        }
+    }
+
+    fn debian_packages() -> Vec<SoftwarePackage> {
+        let mut libc = package("libc6", "2.36-9+deb12u4", PackageSource::Dpkg);
+        libc.source_package = Some("glibc".to_string());
+        vec![
+            package("openssl", "3.0.11-1~deb12u2", PackageSource::Dpkg),
+            libc,
+            package("curl", "7.88.1-10+deb12u5", PackageSource::Dpkg),
+            package("zlib1g", "1:1.2.13.dfsg-1", PackageSource::Dpkg),
+        ]
+    }
+
+    #[test]
+    fn test_ecosystem_parse_and_match() {
+        let eco = Ecosystem::parse("Debian:12");
+        assert_eq!(eco.name, "Debian");
+        assert_eq!(eco.release.as_deref(), Some("12"));
+        assert!(eco.matches("Debian:12"));
+        assert!(!eco.matches("Debian"));
+        assert!(!eco.matches("Debian:11"));
+        assert!(!eco.matches("Ubuntu:22.04:LTS"));
+
+        // A host without VERSION_ID only matches release-less advisories
+        let unknown = Ecosystem::parse("Debian");
+        assert!(!unknown.matches("Debian:11"));
+        assert!(unknown.matches("Debian"));
+
+        let alma = Ecosystem::parse("AlmaLinux:9.3");
+        assert!(alma.matches("AlmaLinux:9"));
+        assert!(!alma.matches("AlmaLinux:8"));
+        assert_eq!(
+            Ecosystem::parse("Red Hat:enterprise_linux:9::appstream")
+                .release
+                .as_deref(),
+            Some("9")
+        );
+    }
+
+    #[test]
+    fn test_first_fix_ignores_non_ecosystem_ranges() {
+        let affected: Affected = serde_json::from_str(
+            r#"{
+                "package": {"ecosystem": "Debian:12", "name": "openssl"},
+                "ranges": [
+                    {"type": "GIT", "events": [{"introduced": "0"}, {"fixed": "9f2b3c1d"}]},
+                    {"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "3.0.12"}]},
+                    {"type": "ECOSYSTEM", "events": [{"introduced": "0"}, {"fixed": "3.0.13-1~deb12u1"}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            first_fix_after(&affected, "3.0.11-1~deb12u2", PackageSource::Dpkg).as_deref(),
+            Some("3.0.13-1~deb12u1")
+        );
+    }
+
+    #[test]
+    fn test_match_debian_advisories() {
+        let matches = match_database(
+            &fixture("advisories.zip"),
+            &debian_packages(),
+            &Ecosystem::parse("Debian:12"),
+            1024 * 1024,
+        )
+        .unwrap();
+
+        let ids: Vec<_> = matches
+            .iter()
+            .map(|m| (m.package.as_str(), m.id.as_str()))
+            .collect();
+        // glibc is already fixed, the Debian 11 advisory does not apply, the zlib range is
+        // closed by last_affected, and the malformed entry is skipped
+        assert_eq!(ids, vec![("curl", "DLA-3001-1"), ("openssl", "DSA-5678-1")]);
+
+        let openssl = &matches[1];
+        assert_eq!(openssl.installed_version, "3.0.11-1~deb12u2");
+        assert_eq!(openssl.fixed_version.as_deref(), Some("3.0.13-1~deb12u1"));
+        assert_eq!(openssl.severity.as_deref(), Some("high"));
+        assert_eq!(openssl.aliases, vec!["CVE-2024-0727"]);
+        assert!(openssl
+            .cvss_vector
+            .as_deref()
+            .unwrap()
+            .starts_with("CVSS:3.1/"));
+
+        // Listed explicitly in `versions`, with no fix available
+        let curl = &matches[0];
+        assert_eq!(curl.fixed_version, None);
+    }
+
+    #[test]
+    fn test_match_source_package_name() {
+        let mut pkgs = debian_packages();
+        pkgs[1].version = "2.36-9+deb12u1".to_string();
+        let matches = match_database(
+            &fixture("advisories.zip"),
+            &pkgs,
+            &Ecosystem::parse("Debian:12"),
+            1024 * 1024,
+        )
+        .unwrap();
+        let libc = matches.iter().find(|m| m.package == "libc6").unwrap();
+        assert_eq!(libc.id, "DSA-5600-1");
+        assert_eq!(libc.fixed_version.as_deref(), Some("2.36-9+deb12u3"));
+    }
+
+    #[test]
+    fn test_match_rpm_version_scheme() {
+        let pkgs = vec![
+            package("bash", "5.1.8-6.el9", PackageSource::Rpm),
+            package("kernel", "5.14.0-362.el9", PackageSource::Rpm),
+        ];
+        let matches = match_database(
+            &fixture("advisories.zip"),
+            &pkgs,
+            &Ecosystem::parse("AlmaLinux:9.3"),
+            1024 * 1024,
+        )
+        .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, "ALSA-2024:0001");
+        assert_eq!(matches[0].fixed_version.as_deref(), Some("5.1.8-9.el9"));
+        assert_eq!(matches[0].severity.as_deref(), Some("Moderate"));
+    }
+
+    #[test]
+    fn test_oversized_advisories_skipped() {
+        let matches = match_database(
+            &fixture("advisories.zip"),
+            &debian_packages(),
+            &Ecosystem::parse("Debian:12"),
+            16,
+        )
+        .unwrap();
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn test_collect_without_database() {
+        let cfg = VulnerabilityConfig::default();
+        assert!(collect(Path::new("/nonexistent"), &debian_packages(), &cfg)
+            .unwrap()
+            .is_empty());
+    }
+}
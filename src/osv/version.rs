@@ -0,0 +1,258 @@
+//! Package version ordering for the dpkg and RPM version schemes.
+
+use std::cmp::Ordering;
+
+use crate::models::PackageSource;
+
+/// Compare two versions using the scheme of the given package database.
+pub fn compare(source: PackageSource, a: &str, b: &str) -> Ordering {
+    match source {
+        PackageSource::Dpkg => compare_dpkg(a, b),
+        PackageSource::Rpm => compare_rpm(a, b),
+    }
+}
+
+/// Compare Debian versions (`[epoch:]upstream[-revision]`) as `dpkg --compare-versions` does.
+pub fn compare_dpkg(a: &str, b: &str) -> Ordering {
+    let (ea, ua, ra) = split_dpkg(a);
+    let (eb, ub, rb) = split_dpkg(b);
+    ea.cmp(&eb)
+        .then_with(|| dpkg_verrevcmp(ua, ub))
+        .then_with(|| dpkg_verrevcmp(ra, rb))
+}
+
+fn split_dpkg(v: &str) -> (u64, &str, &str) {
+    let (epoch, rest) = match v.split_once(':') {
+        Some((e, rest)) => (e.parse().unwrap_or(0), rest),
+        None => (0, v),
+    };
+    match rest.rsplit_once('-') {
+        Some((upstream, revision)) => (epoch, upstream, revision),
+        None => (epoch, rest, ""),
+    }
+}
+
+/// Sort weight of a non-digit character: `~` before everything (even the end of the string),
+/// then letters, then all other characters.
+fn dpkg_order(c: Option<u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(b'~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+fn dpkg_verrevcmp(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() || j < b.len() {
+        // Non-digit prefix, compared character by character
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let ac = dpkg_order(a.get(i).copied());
+            let bc = dpkg_order(b.get(j).copied());
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            i += 1;
+            j += 1;
+        }
+
+        // Digit run, compared numerically
+        let start_a = i;
+        while i < a.len() && a[i].is_ascii_digit() {
+            i += 1;
+        }
+        let start_b = j;
+        while j < b.len() && b[j].is_ascii_digit() {
+            j += 1;
+        }
+        let ord = compare_numeric(&a[start_a..i], &b[start_b..j]);
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Compare RPM `[epoch:]version[-release]` strings as rpm's `rpmvercmp` does for each part.
+pub fn compare_rpm(a: &str, b: &str) -> Ordering {
+    let (ea, va, ra) = split_rpm(a);
+    let (eb, vb, rb) = split_rpm(b);
+    ea.cmp(&eb)
+        .then_with(|| rpmvercmp(va, vb))
+        .then_with(|| match (ra, rb) {
+            (Some(ra), Some(rb)) => rpmvercmp(ra, rb),
+            // A missing release matches any release
+            _ => Ordering::Equal,
+        })
+}
+
+fn split_rpm(v: &str) -> (u64, &str, Option<&str>) {
+    let (epoch, rest) = match v.split_once(':') {
+        Some((e, rest)) => (e.parse().unwrap_or(0), rest),
+        None => (0, v),
+    };
+    match rest.rsplit_once('-') {
+        Some((version, release)) => (epoch, version, Some(release)),
+        None => (epoch, rest, None),
+    }
+}
+
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    let separator = |c: u8| !c.is_ascii_alphanumeric() && c != b'~' && c != b'^';
+
+    loop {
+        while i < a.len() && separator(a[i]) {
+            i += 1;
+        }
+        while j < b.len() && separator(b[j]) {
+            j += 1;
+        }
+
+        // Tilde sorts before everything, including the end of the string
+        if a.get(i) == Some(&b'~') || b.get(j) == Some(&b'~') {
+            if a.get(i) != Some(&b'~') {
+                return Ordering::Greater;
+            }
+            if b.get(j) != Some(&b'~') {
+                return Ordering::Less;
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // Caret sorts after the end of the string but before anything else
+        if a.get(i) == Some(&b'^') || b.get(j) == Some(&b'^') {
+            if i >= a.len() {
+                return Ordering::Less;
+            }
+            if j >= b.len() {
+                return Ordering::Greater;
+            }
+            if a[i] != b'^' {
+                return Ordering::Greater;
+            }
+            if b[j] != b'^' {
+                return Ordering::Less;
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        if i >= a.len() || j >= b.len() {
+            break;
+        }
+
+        let (start_a, start_b) = (i, j);
+        let numeric = a[i].is_ascii_digit();
+        let in_segment = |c: u8| {
+            if numeric {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        };
+        while i < a.len() && in_segment(a[i]) {
+            i += 1;
+        }
+        while j < b.len() && in_segment(b[j]) {
+            j += 1;
+        }
+
+        // Segments of different types: numeric is newer than alphabetic
+        if j == start_b {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let ord = if numeric {
+            compare_numeric(&a[start_a..i], &b[start_b..j])
+        } else {
+            a[start_a..i].cmp(&b[start_b..j])
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+
+    match (i >= a.len(), j >= b.len()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        _ => Ordering::Greater,
+    }
+}
+
+/// Compare two ASCII digit runs by numeric value without overflowing.
+fn compare_numeric(a: &[u8], b: &[u8]) -> Ordering {
+    let trim = |s: &[u8]| -> usize { s.iter().take_while(|c| **c == b'0').count() };
+    let a = &a[trim(a)..];
+    let b = &b[trim(b)..];
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Ordering::{Equal, Greater, Less};
+
+    #[test]
+    fn test_dpkg_ordering() {
+        let cases = [
+            ("1.0", "1.0", Equal),
+            ("1.0-1", "1.0-2", Less),
+            ("1:1.0", "2.0", Greater),
+            ("1.0~rc1", "1.0", Less),
+            ("1.0~rc1", "1.0~rc2", Less),
+            ("1.0+b1", "1.0", Greater),
+            ("1.0a", "1.0+", Less),
+            ("3.0.11-1~deb12u2", "3.0.13-1~deb12u1", Less),
+            ("2.36-9+deb12u4", "2.36-9+deb12u3", Greater),
+            ("1.10", "1.9", Greater),
+            ("1.001", "1.1", Equal),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_dpkg(a, b), expected, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_rpm_ordering() {
+        let cases = [
+            ("1.0-1", "1.0-1", Equal),
+            ("1.0-1", "1.0-2", Less),
+            ("1:1.0-1", "2.0-1", Greater),
+            ("1.0~rc1-1", "1.0-1", Less),
+            ("1.0^git1-1", "1.0-1", Greater),
+            ("1.0^git1-1", "1.0.1-1", Less),
+            ("5.2.26-3.fc40", "5.2.26-4.fc40", Less),
+            ("1.0a-1", "1.0.1-1", Less),
+            ("1.10-1", "1.9-1", Greater),
+            ("2.0", "2.0-5", Equal),
+            ("1.0.el9", "1.0.el9_1", Less),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_rpm(a, b), expected, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_compare_dispatches_on_source() {
+        // RPM treats a missing release as a wildcard; dpkg treats it as the empty revision
+        assert_eq!(compare(PackageSource::Dpkg, "2.0", "2.0-5"), Less);
+        assert_eq!(compare(PackageSource::Rpm, "2.0", "2.0-5"), Equal);
+    }
+}
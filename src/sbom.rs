@@ -0,0 +1,227 @@
+//! CycloneDX SBOM rendering of the installed software inventory.
+//!
+//! Produces a CycloneDX 1.5 JSON document with one `library` component per package. Each
+//! component carries a package URL (purl) in the ecosystem of its package database, namespaced
+//! by the distribution ID from `/etc/os-release` (see `inventory::os_release`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{PackageSource, SoftwarePackage};
+
+const SPEC_VERSION: &str = "1.5";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Bom {
+    pub bom_format: String,
+    pub spec_version: String,
+    pub version: u32,
+    pub metadata: BomMetadata,
+    pub components: Vec<BomComponent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BomMetadata {
+    pub timestamp: String,
+    pub tools: BomTools,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BomTools {
+    pub components: Vec<BomComponent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BomComponent {
+    #[serde(rename = "type")]
+    pub component_type: String,
+    #[serde(rename = "bom-ref", skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+    pub name: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<BomProperty>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BomProperty {
+    pub name: String,
+    pub value: String,
+}
+
+/// Render a CycloneDX BOM for the given packages.
+///
+/// `distro` is the os-release `ID` used as the purl namespace; without it purls have no namespace.
+pub fn render(packages: &[SoftwarePackage], distro: Option<&str>, timestamp: &str) -> Bom {
+    let tool = BomComponent {
+        component_type: "application".to_string(),
+        bom_ref: None,
+        name: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        publisher: None,
+        purl: None,
+        properties: Vec::new(),
+    };
+
+    let components = packages
+        .iter()
+        .map(|pkg| {
+            let purl = purl(pkg, distro);
+            let mut properties = vec![BomProperty {
+                name: "inventory-agent:package:source".to_string(),
+                value: source_name(pkg.source).to_string(),
+            }];
+            if let Some(date) = &pkg.install_date {
+                properties.push(BomProperty {
+                    name: "inventory-agent:package:install_date".to_string(),
+                    value: date.clone(),
+                });
+            }
+            BomComponent {
+                component_type: "library".to_string(),
+                bom_ref: Some(purl.clone()),
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                publisher: pkg.vendor.clone(),
+                purl: Some(purl),
+                properties,
+            }
+        })
+        .collect();
+
+    Bom {
+        bom_format: "CycloneDX".to_string(),
+        spec_version: SPEC_VERSION.to_string(),
+        version: 1,
+        metadata: BomMetadata {
+            timestamp: timestamp.to_string(),
+            tools: BomTools {
+                components: vec![tool],
+            },
+        },
+        components,
+    }
+}
+
+/// Build the package URL for a package, e.g. `pkg:deb/debian/openssl@3.0.11-1~deb12u2?arch=amd64`.
+///
+/// RPM epochs go in the `epoch` qualifier as the purl spec requires; Debian epochs stay part of
+/// the version string.
+pub fn purl(pkg: &SoftwarePackage, distro: Option<&str>) -> String {
+    let mut version = pkg.version.as_str();
+    let mut qualifiers = Vec::new();
+
+    if let Some(arch) = &pkg.arch {
+        qualifiers.push(format!("arch={}", encode(arch)));
+    }
+    if pkg.source == PackageSource::Rpm {
+        if let Some((epoch, rest)) = version.split_once(':') {
+            qualifiers.push(format!("epoch={}", encode(epoch)));
+            version = rest;
+        }
+    }
+
+    let mut purl = format!("pkg:{}/", source_name(pkg.source));
+    if let Some(distro) = distro {
+        purl.push_str(&encode(&distro.to_ascii_lowercase()));
+        purl.push('/');
+    }
+    purl.push_str(&encode(&pkg.name));
+    purl.push('@');
+    purl.push_str(&encode(version));
+    if !qualifiers.is_empty() {
+        purl.push('?');
+        purl.push_str(&qualifiers.join("&"));
+    }
+    purl
+}
+
+fn source_name(source: PackageSource) -> &'static str {
+    match source {
+        PackageSource::Dpkg => "deb",
+        PackageSource::Rpm => "rpm",
+    }
+}
+
+/// Percent-encode everything outside the purl unreserved set.
+fn encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str, source: PackageSource) -> SoftwarePackage {
+        SoftwarePackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: Some("x86_64".to_string()),
+            vendor: Some("Fedora Project".to_string()),
+            source,
+            source_package: None,
+            install_date: Some("2024-05-01T12:00:00+00:00".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_purl_deb_keeps_epoch_in_version() {
+        let mut pkg = package("vim", "2:9.0.1378-2+b1", PackageSource::Dpkg);
+        pkg.arch = Some("amd64".to_string());
+        assert_eq!(
+            purl(&pkg, Some("debian")),
+            "pkg:deb/debian/vim@2%3A9.0.1378-2%2Bb1?arch=amd64"
+        );
+    }
+
+    #[test]
+    fn test_purl_rpm_epoch_qualifier() {
+        let pkg = package("kernel-core", "1:6.8.9-300.fc40", PackageSource::Rpm);
+        assert_eq!(
+            purl(&pkg, Some("fedora")),
+            "pkg:rpm/fedora/kernel-core@6.8.9-300.fc40?arch=x86_64&epoch=1"
+        );
+    }
+
+    #[test]
+    fn test_purl_without_distro_or_arch() {
+        let mut pkg = package("bash", "5.2.26-3.fc40", PackageSource::Rpm);
+        pkg.arch = None;
+        assert_eq!(purl(&pkg, None), "pkg:rpm/bash@5.2.26-3.fc40");
+    }
+
+    #[test]
+    fn test_render_bom() {
+        let pkgs = vec![package("bash", "5.2.26-3.fc40", PackageSource::Rpm)];
+        let bom = render(&pkgs, Some("fedora"), "2025-12-18T10:00:00Z");
+
+        let json = serde_json::to_value(&bom).unwrap();
+        assert_eq!(json["bomFormat"], "CycloneDX");
+        assert_eq!(json["specVersion"], "1.5");
+        assert_eq!(
+            json["metadata"]["tools"]["components"][0]["name"],
+            "inventory-agent"
+        );
+
+        let component = &json["components"][0];
+        assert_eq!(component["type"], "library");
+        assert_eq!(component["publisher"], "Fedora Project");
+        assert_eq!(component["bom-ref"], component["purl"]);
+        assert_eq!(
+            component["properties"][1]["value"],
+            "2024-05-01T12:00:00+00:00"
+        );
+    }
+}
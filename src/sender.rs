@@ -43,6 +43,7 @@ mod tests {
             laptop_serial: "TEST-SERIAL".to_string(),
             drives: vec![],
             timestamp_utc: "2025-12-18T10:00:00Z".to_string(),
+            ..Default::default()
         }
     }
 
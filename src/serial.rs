@@ -0,0 +1,126 @@
+//! Hardware serial number normalization.
+//!
+//! Serials come back from firmware and WMI padded with spaces or NULs, hex-encoded by some NVMe
+//! and SATA drivers, or left at OEM template values. `normalize` turns them into either a usable
+//! serial or `None`; callers keep the raw value next to it.
+
+use crate::config::SerialConfig;
+use crate::inventory::glob_match;
+
+/// Shortest hex string treated as encoded text; shorter ones are more likely real serials.
+const MIN_HEX_LEN: usize = 16;
+
+/// Normalize `raw`, returning `None` for empty, all-zero and placeholder values.
+pub fn normalize(raw: &str, cfg: &SerialConfig) -> Option<String> {
+    let trimmed = trim(raw);
+    let decoded = decode_hex(trimmed);
+    let value = decoded.as_deref().map(trim).unwrap_or(trimmed);
+
+    // Also catches EUI-style all-zero identifiers such as "0000_0000_0000_0000."
+    if value
+        .chars()
+        .filter(|c| !"-_. ".contains(*c))
+        .all(|c| c == '0')
+    {
+        return None;
+    }
+    let lower = value.to_lowercase();
+    if cfg
+        .placeholders
+        .iter()
+        .any(|p| glob_match(&p.to_lowercase(), &lower))
+    {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+fn trim(value: &str) -> &str {
+    value.trim_matches(|c: char| c.is_whitespace() || c.is_control())
+}
+
+/// Decode a serial reported as the hex of its ASCII bytes, e.g. `2020202057442d5758...`.
+///
+/// Only applied when every byte decodes to printable ASCII, which real hex serials almost never
+/// do.
+fn decode_hex(value: &str) -> Option<String> {
+    if value.len() < MIN_HEX_LEN
+        || !value.len().is_multiple_of(2)
+        || !value.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    let bytes: Vec<u8> = (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let printable = bytes.iter().all(|b| (0x20..0x7f).contains(b));
+    let has_alphanumeric = bytes.iter().any(|b| b.is_ascii_alphanumeric());
+    (printable && has_alphanumeric).then(|| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize_default(raw: &str) -> Option<String> {
+        normalize(raw, &SerialConfig::default())
+    }
+
+    #[test]
+    fn test_trims_padding() {
+        assert_eq!(
+            normalize_default("   S4EVNX0M123456  ").as_deref(),
+            Some("S4EVNX0M123456")
+        );
+        assert_eq!(
+            normalize_default("PF1ABCDE\0\0\0").as_deref(),
+            Some("PF1ABCDE")
+        );
+    }
+
+    #[test]
+    fn test_decodes_hex() {
+        // "      WD-WX31A1234567" as reported by some SATA drivers
+        assert_eq!(
+            normalize_default("20202020202057442d575833314131323334353637").as_deref(),
+            Some("WD-WX31A1234567")
+        );
+        // Real hex serials decode to unprintable bytes and are kept
+        assert_eq!(
+            normalize_default("0025385b71b0a1c2").as_deref(),
+            Some("0025385b71b0a1c2")
+        );
+        assert_eq!(
+            normalize_default("4142434445").as_deref(),
+            Some("4142434445")
+        );
+    }
+
+    #[test]
+    fn test_rejects_zero_and_placeholders() {
+        assert_eq!(normalize_default(""), None);
+        assert_eq!(normalize_default("    "), None);
+        assert_eq!(normalize_default("00000000"), None);
+        assert_eq!(normalize_default("0000_0000_0000_0000."), None);
+        assert_eq!(normalize_default("To be filled by O.E.M."), None);
+        assert_eq!(normalize_default("default string"), None);
+        assert_eq!(normalize_default("System Serial Number"), None);
+        // Zeros are also caught after decoding
+        assert_eq!(normalize_default("30303030303030303030303030303030"), None);
+    }
+
+    #[test]
+    fn test_configured_patterns() {
+        let cfg = SerialConfig {
+            placeholders: vec!["CHASSIS*".to_string()],
+        };
+        assert_eq!(normalize("Chassis Serial 1", &cfg), None);
+        // The configured list replaces the built-in one
+        assert_eq!(
+            normalize("Default string", &cfg).as_deref(),
+            Some("Default string")
+        );
+    }
+}
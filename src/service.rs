@@ -8,7 +8,7 @@ use windows_service::service::{
 };
 use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
 
-use crate::{collector, config, sender};
+use crate::{collector, config, sender, state};
 
 const SERVICE_NAME: &str = "InventoryAgent";
 
@@ -32,7 +32,7 @@ fn run_service() -> Result<()> {
     let cfg = config::load_config()?;
 
     // Validate api_url is set
-    let api_url = cfg.api_url.ok_or_else(|| {
+    let api_url = cfg.api_url.clone().ok_or_else(|| {
         anyhow::anyhow!("INVENTORY_API_URL not set (required in config.toml or environment)")
     })?;
 
@@ -61,20 +61,32 @@ fn run_service() -> Result<()> {
         process_id: None,
     })?;
 
+    // Login history since the last accepted check-in, also across restarts
+    let state_path = state::last_checkin_path()?;
+
     // Run interval loop on a tokio runtime
     let rt = Runtime::new().context("tokio runtime create failed")?;
 
     rt.block_on(async move {
+        let mut last_checkin = state::load_last_checkin(&state_path);
         loop {
             // Check for shutdown without blocking the async loop too aggressively
             if shutdown_rx.try_recv().is_ok() {
                 break;
             }
 
-            match collector::collect() {
+            let started = chrono::Utc::now();
+            match collector::collect(&cfg, last_checkin) {
                 Ok(checkin) => {
-                    if let Err(_e) = sender::send(&checkin, &api_url, cfg.tls_insecure).await {
-                        // TODO: write to Windows Event Log
+                    match sender::send(&checkin, &api_url, cfg.tls_insecure).await {
+                        Ok(_) => {
+                            last_checkin = Some(started);
+                            // TODO: write failures to Windows Event Log
+                            let _ = state::save_last_checkin(&state_path, started);
+                        }
+                        Err(_e) => {
+                            // TODO: write to Windows Event Log
+                        }
                     }
                 }
                 Err(_e) => {
@@ -0,0 +1,61 @@
+//! Agent state kept across restarts, stored next to the executable.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::config::exe_dir;
+
+/// File holding the start time of the last check-in the API accepted, in RFC 3339.
+pub fn last_checkin_path() -> Result<PathBuf> {
+    Ok(exe_dir()?.join("last_checkin"))
+}
+
+/// The persisted last check-in time. A missing or unreadable file means there was none, so the
+/// next check-in reports the full login history window again.
+pub fn load_last_checkin(path: &Path) -> Option<DateTime<Utc>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Persist `time` as the last check-in. The file is replaced through a rename so that a crash
+/// cannot leave a partial timestamp behind.
+pub fn save_last_checkin(path: &Path, time: DateTime<Utc>) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, time.to_rfc3339())
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_last_checkin_round_trip() {
+        let dir =
+            std::env::temp_dir().join(format!("inventory-agent-state-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("last_checkin");
+        assert_eq!(load_last_checkin(&path), None);
+
+        let time = Utc.with_ymd_and_hms(2025, 10, 9, 12, 0, 0).unwrap();
+        save_last_checkin(&path, time).unwrap();
+        assert_eq!(load_last_checkin(&path), Some(time));
+
+        // Overwritten on the next successful check-in
+        save_last_checkin(&path, time + chrono::Duration::minutes(30)).unwrap();
+        assert_eq!(
+            load_last_checkin(&path),
+            Some(time + chrono::Duration::minutes(30))
+        );
+
+        std::fs::write(&path, "garbage").unwrap();
+        assert_eq!(load_last_checkin(&path), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}